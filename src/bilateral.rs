@@ -0,0 +1,162 @@
+//! Bilateral visa-waiver agreements allowing longer stays in a single member country.
+
+use crate::membership::country_code;
+use crate::{DateIntervalVec, Engine, Trip};
+use anyhow::{Context, Result};
+use chrono::{Days, NaiveDate};
+use serde::{Deserialize, Deserializer, Serialize};
+use std::str::FromStr;
+
+/// Agreement allowing nationals of one country to spend more days in a member country than the
+/// rules of the zone allow.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Agreement {
+    /// ISO 3166-1 alpha-2 code of the nationality of the traveller.
+    #[serde(deserialize_with = "deserialize_nationality")]
+    pub nationality: String,
+    /// Code of the member country the agreement was made with.
+    #[serde(deserialize_with = "deserialize_country")]
+    pub country: String,
+    /// Days that can be spent in the country beyond the allowance of the zone.
+    pub days: usize,
+}
+
+/// Commonly cited agreements. The conditions of the agreements differ and should be confirmed with
+/// the authorities of the country before relying on them.
+const AGREEMENTS: &[(&str, &str, usize)] = &[
+    ("AU", "DK", 90),
+    ("CA", "DK", 90),
+    ("NZ", "DK", 90),
+    ("US", "DK", 90),
+    ("US", "PL", 90),
+];
+
+/// Built-in agreements.
+pub fn agreements() -> Vec<Agreement> {
+    AGREEMENTS
+        .iter()
+        .map(|&(nationality, country, days)| Agreement {
+            nationality: nationality.to_string(),
+            country: country.to_string(),
+            days,
+        })
+        .collect()
+}
+
+impl FromStr for Agreement {
+    type Err = anyhow::Error;
+
+    /// Parses an agreement written as `NATIONALITY:COUNTRY:DAYS`, for example `US:PL:90`.
+    fn from_str(s: &str) -> Result<Self> {
+        let fields: Vec<_> = s.split(':').map(str::trim).collect();
+        let &[nationality, country, days] = fields.as_slice() else {
+            anyhow::bail!("Expected NATIONALITY:COUNTRY:DAYS, got '{s}'");
+        };
+        Ok(Self {
+            nationality: nationality.to_uppercase(),
+            country: country_code(country),
+            days: days
+                .parse()
+                .with_context(|| format!("Invalid number of days '{days}'"))?,
+        })
+    }
+}
+
+/// Reads the nationality code in upper case.
+fn deserialize_nationality<'de, D: Deserializer<'de>>(deserializer: D) -> Result<String, D::Error> {
+    Ok(String::deserialize(deserializer)?.trim().to_uppercase())
+}
+
+/// Reads the country by its code or name, as the code.
+fn deserialize_country<'de, D: Deserializer<'de>>(deserializer: D) -> Result<String, D::Error> {
+    Ok(country_code(&String::deserialize(deserializer)?))
+}
+
+/// Days over the allowance spent in one country.
+#[derive(Debug, Clone, Serialize)]
+pub struct CountryExcess {
+    /// Country code, or `None` for trips without a country.
+    pub country: Option<String>,
+    pub days: usize,
+    /// Agreement with the country, if the traveller's nationality has one.
+    pub agreement: Option<Agreement>,
+}
+
+/// Whether the days over the allowance are covered by bilateral agreements.
+#[derive(Debug, Clone, Serialize)]
+pub struct BilateralCheck {
+    pub nationality: String,
+    pub days_over: usize,
+    /// Days over the allowance by country, in the order the countries were visited.
+    pub countries: Vec<CountryExcess>,
+    /// Whether every day over the allowance was spent in a country with an agreement, within the
+    /// days the agreement allows.
+    pub covered: bool,
+}
+
+impl Engine {
+    /// Checks whether the days over the allowance in the control period ending on `end_date` are
+    /// covered by agreements of `nationality`. Returns `None` if the allowance is not exceeded, or
+    /// if the rule only limits the length of a single stay.
+    ///
+    /// The days in the control period are counted in order, and the days after the allowance is
+    /// used up have to be spent in countries with an agreement. This is an approximation: the
+    /// agreements count days in their own ways, which this check does not model.
+    pub fn bilateral_check(
+        &self,
+        trips: &[Trip],
+        end_date: NaiveDate,
+        nationality: &str,
+        agreements: &[Agreement],
+    ) -> Result<Option<BilateralCheck>> {
+        let Some(allowed) = self.allowed() else {
+            return Ok(None);
+        };
+        let stays = DateIntervalVec::from_trips(trips, end_date);
+        let check = self.check(&stays, end_date)?;
+        if check.days_used <= allowed {
+            return Ok(None);
+        }
+
+        let mut countries: Vec<CountryExcess> = Vec::new();
+        let mut days_used = 0;
+        let mut date = check.control_period.start();
+        while date <= end_date {
+            // On a day of changing countries, the day counts for the country entered.
+            let trip = trips
+                .iter()
+                .filter(|trip| trip.interval(end_date).is_some_and(|di| di.contains(date)))
+                .max_by_key(|trip| trip.entry);
+            if let Some(trip) = trip {
+                days_used += 1;
+                if days_used > allowed {
+                    let country = trip.info.country.as_deref().map(country_code);
+                    if let Some(excess) = countries.iter_mut().find(|c| c.country == country) {
+                        excess.days += 1;
+                    } else {
+                        let agreement = agreements.iter().find(|a| {
+                            a.nationality.eq_ignore_ascii_case(nationality)
+                                && Some(&a.country) == country.as_ref()
+                        });
+                        countries.push(CountryExcess {
+                            country,
+                            days: 1,
+                            agreement: agreement.cloned(),
+                        });
+                    }
+                }
+            }
+            date = date + Days::new(1);
+        }
+
+        let covered = countries
+            .iter()
+            .all(|c| c.agreement.as_ref().is_some_and(|a| c.days <= a.days));
+        Ok(Some(BilateralCheck {
+            nationality: nationality.to_uppercase(),
+            days_over: days_used - allowed,
+            countries,
+            covered,
+        }))
+    }
+}
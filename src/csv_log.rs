@@ -0,0 +1,219 @@
+//! Import of trip logs in CSV format.
+
+use crate::{parse_date, Trip, TripInfo};
+use anyhow::{Context, Result};
+use std::fmt;
+use std::io::Read;
+use std::str::FromStr;
+
+/// Trip field that can be read from a CSV column.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Field {
+    Entry,
+    Exit,
+    Label,
+    Country,
+    Purpose,
+    Notes,
+    Border,
+}
+
+impl Field {
+    const ALL: [Field; 7] = [
+        Self::Entry,
+        Self::Exit,
+        Self::Label,
+        Self::Country,
+        Self::Purpose,
+        Self::Notes,
+        Self::Border,
+    ];
+
+    /// Header names recognised for the field, in lower case.
+    fn aliases(&self) -> &'static [&'static str] {
+        match self {
+            Self::Entry => &["entry", "from", "start", "arrival", "arrived", "in"],
+            Self::Exit => &["exit", "to", "end", "departure", "departed", "out"],
+            Self::Label => &["label", "name", "trip", "title"],
+            Self::Country => &["country"],
+            Self::Purpose => &["purpose", "reason"],
+            Self::Notes => &["notes", "note", "comment", "comments"],
+            Self::Border => &["border", "crossing"],
+        }
+    }
+
+    /// Position of the field in a file without a header.
+    fn default_index(&self) -> Option<usize> {
+        match self {
+            Self::Entry => Some(0),
+            Self::Exit => Some(1),
+            Self::Country => Some(2),
+            Self::Purpose => Some(3),
+            Self::Notes => Some(4),
+            Self::Label | Self::Border => None,
+        }
+    }
+}
+
+impl fmt::Display for Field {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.aliases()[0])
+    }
+}
+
+impl FromStr for Field {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let s = s.trim().to_lowercase();
+        Self::ALL
+            .into_iter()
+            .find(|field| field.aliases()[0] == s)
+            .with_context(|| format!("Unknown trip field '{s}'"))
+    }
+}
+
+/// Column of a field given by its header name or by its position counted from 1.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Column {
+    Name(String),
+    Position(usize),
+}
+
+/// Mapping of trip fields to CSV columns, written as `field=column,...`, for example
+/// `entry=Arrived,exit=Departed,country=3`. Unmapped fields are looked up by their usual header
+/// names, or taken from the default positions `entry,exit,country,purpose,notes` if the file has no
+/// header.
+#[derive(Debug, Clone, Default)]
+pub struct ColumnMap(Vec<(Field, Column)>);
+
+impl FromStr for ColumnMap {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let mut columns = Vec::new();
+        for mapping in s.split(',').filter(|m| !m.trim().is_empty()) {
+            let Some((field, column)) = mapping.split_once('=') else {
+                anyhow::bail!("Expected field=column, got '{mapping}'");
+            };
+            let field = field.parse()?;
+            let column = column.trim();
+            let column = match column.parse::<usize>() {
+                Ok(0) => anyhow::bail!("Column positions start from 1"),
+                Ok(position) => Column::Position(position),
+                Err(_) => Column::Name(column.to_string()),
+            };
+            columns.push((field, column));
+        }
+        Ok(Self(columns))
+    }
+}
+
+impl ColumnMap {
+    fn get(&self, field: Field) -> Option<&Column> {
+        self.0.iter().find(|(f, _)| *f == field).map(|(_, c)| c)
+    }
+
+    fn has_names(&self) -> bool {
+        self.0.iter().any(|(_, c)| matches!(c, Column::Name(_)))
+    }
+
+    /// Finds the column index of every field. Entry and exit columns are required.
+    fn resolve(&self, header: Option<&csv::StringRecord>) -> Result<Vec<(Field, usize)>> {
+        let mut indices = Vec::new();
+        for field in Field::ALL {
+            let index = match (self.get(field), header) {
+                (Some(Column::Position(position)), _) => Some(position - 1),
+                (Some(Column::Name(name)), Some(header)) => Some(
+                    header
+                        .iter()
+                        .position(|h| h.eq_ignore_ascii_case(name))
+                        .with_context(|| format!("Column '{name}' not found in the header"))?,
+                ),
+                (Some(Column::Name(name)), None) => {
+                    anyhow::bail!("Column '{name}' given by name but the file has no header")
+                }
+                (None, Some(header)) => header
+                    .iter()
+                    .position(|h| field.aliases().contains(&h.to_lowercase().as_str())),
+                (None, None) => field.default_index(),
+            };
+            match index {
+                Some(index) => indices.push((field, index)),
+                None if matches!(field, Field::Entry | Field::Exit) => {
+                    anyhow::bail!("No {field} column found")
+                }
+                None => {}
+            }
+        }
+        Ok(indices)
+    }
+}
+
+/// Reads trips from a CSV trip log.
+///
+/// The first row is a header if any column is mapped by name or if its entry column is not a date.
+/// An empty exit date means a trip that has not ended yet.
+pub fn parse_csv_trips<R: Read>(reader: R, columns: &ColumnMap) -> Result<Vec<Trip>> {
+    let mut reader = csv::ReaderBuilder::new()
+        .has_headers(false)
+        .flexible(true)
+        .trim(csv::Trim::All)
+        .from_reader(reader);
+    let mut records = reader.records();
+    let Some(first) = records.next().transpose()? else {
+        return Ok(Vec::new());
+    };
+
+    let entry_index = match columns.get(Field::Entry) {
+        Some(Column::Position(position)) => position - 1,
+        _ => 0,
+    };
+    let is_header = columns.has_names()
+        || first
+            .get(entry_index)
+            .is_none_or(|s| parse_date(s).is_err());
+    let indices = columns.resolve(is_header.then_some(&first))?;
+
+    let mut trips = Vec::new();
+    let first = if is_header { None } else { Some(Ok(first)) };
+    for record in first.into_iter().chain(records) {
+        let record = record?;
+        let row = record.position().map_or(0, |p| p.line() as usize);
+        trips.push(
+            parse_record(&record, &indices)
+                .with_context(|| format!("Row {row}"))?
+                .at_line(row),
+        );
+    }
+    Ok(trips)
+}
+
+fn parse_record(record: &csv::StringRecord, indices: &[(Field, usize)]) -> Result<Trip> {
+    let mut entry = None;
+    let mut exit = None;
+    let mut info = TripInfo::default();
+    for &(field, index) in indices {
+        let value = record.get(index).unwrap_or_default();
+        let context = || format!("column {} ({field})", index + 1);
+        let text = (!value.is_empty()).then(|| value.to_string());
+        match field {
+            Field::Entry => entry = Some(parse_date(value).with_context(context)?),
+            Field::Exit if value.is_empty() => {}
+            Field::Exit => exit = Some(parse_date(value).with_context(context)?),
+            Field::Label => info.label = text,
+            Field::Country => info.country = text,
+            Field::Purpose => info.purpose = text,
+            Field::Notes => info.notes = text,
+            Field::Border if value.is_empty() => {}
+            Field::Border => info.border = Some(value.parse().with_context(context)?),
+        }
+    }
+    let entry = entry.expect("entry column is resolved");
+    let trip = if let Some(exit) = exit {
+        Trip::new(entry, exit)?
+    } else {
+        Trip::open(entry)
+    };
+    Ok(trip.with_info(info))
+}
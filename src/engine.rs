@@ -0,0 +1,236 @@
+//! Rolling-window engine.
+
+use crate::{DateInterval, DateIntervalVec, ALLOWED_DAYS, CONTROL_PERIOD_DAYS};
+use anyhow::{Context, Result};
+use chrono::{Days, NaiveDate};
+use serde::Serialize;
+use std::fmt;
+
+/// Whether the spent days fit into the allowance.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Verdict {
+    Within,
+    Exceeds,
+}
+
+impl fmt::Display for Verdict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Within => write!(f, "are within"),
+            Self::Exceeds => write!(f, "exceed"),
+        }
+    }
+}
+
+/// Result of checking the stays against the control period ending on a given date.
+#[derive(Debug, Clone, Serialize)]
+pub struct WindowCheck {
+    /// Control period the stays were counted in.
+    pub control_period: DateInterval,
+    /// Stays clipped to the control period.
+    pub intervals: DateIntervalVec,
+    /// Days spent in the control period.
+    pub days_used: usize,
+    /// Days still allowed in the control period, and in the current stay if the length of a single
+    /// stay is limited. Zero if the allowance is exceeded.
+    pub days_remaining: usize,
+    /// Days over the allowance or over the maximum length of the current stay, whichever is more.
+    pub days_over: usize,
+    pub verdict: Verdict,
+}
+
+/// Rolling-window rule of the form "`allowed` days out of `period`", optionally limiting the length
+/// of a single stay to `max_stay` days. Without an allowance only the length of a single stay is
+/// limited.
+#[derive(Debug, Copy, Clone, Serialize)]
+pub struct Engine {
+    period: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    allowed: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    max_stay: Option<usize>,
+}
+
+impl Default for Engine {
+    fn default() -> Self {
+        Self::new(CONTROL_PERIOD_DAYS, ALLOWED_DAYS)
+    }
+}
+
+impl fmt::Display for Engine {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (self.allowed, self.max_stay) {
+            (Some(allowed), None) => write!(f, "{allowed} days in any {}", self.period),
+            (Some(allowed), Some(max_stay)) => write!(
+                f,
+                "{allowed} days in any {}, up to {max_stay} days per stay",
+                self.period
+            ),
+            (None, _) => write!(f, "up to {} days per stay", self.limit()),
+        }
+    }
+}
+
+impl Engine {
+    pub fn new(period: usize, allowed: usize) -> Self {
+        Self {
+            period,
+            allowed: Some(allowed),
+            max_stay: None,
+        }
+    }
+
+    /// Rule that only limits the length of a single stay to `max_stay` days, with a control period
+    /// as long as the stay.
+    pub fn single_stay(max_stay: usize) -> Self {
+        Self {
+            period: max_stay,
+            allowed: None,
+            max_stay: Some(max_stay),
+        }
+    }
+
+    /// Limits the length of a single stay to `max_stay` days.
+    pub fn with_max_stay(mut self, max_stay: Option<usize>) -> Self {
+        self.max_stay = max_stay;
+        self
+    }
+
+    pub fn period(&self) -> usize {
+        self.period
+    }
+
+    pub fn allowed(&self) -> Option<usize> {
+        self.allowed
+    }
+
+    pub fn max_stay_limit(&self) -> Option<usize> {
+        self.max_stay
+    }
+
+    /// Most days a single stay can last: the allowance or the maximum stay, whichever is smaller.
+    pub fn limit(&self) -> usize {
+        self.allowed
+            .into_iter()
+            .chain(self.max_stay)
+            .min()
+            .unwrap_or(self.period)
+    }
+
+    /// Control period of `period` days ending on `end_date`, `end_date` included.
+    pub fn control_period(&self, end_date: NaiveDate) -> Result<DateInterval> {
+        if self.period == 0 {
+            anyhow::bail!("The control period must be at least one day long");
+        }
+        let start_date = end_date
+            .checked_sub_days(Days::new(self.period as u64 - 1))
+            .with_context(|| format!("The control period of {} days is too long", self.period))?;
+        DateInterval::new(start_date, end_date)
+    }
+
+    /// Counts the days of `stays` in the control period ending on `end_date`.
+    pub fn check(&self, stays: &DateIntervalVec, end_date: NaiveDate) -> Result<WindowCheck> {
+        let control_period = self.control_period(end_date)?;
+        let intervals = stays.clip(control_period);
+        let days_used = intervals.num_spent_days();
+        let (mut days_remaining, mut days_over) = match self.allowed {
+            Some(allowed) => (
+                allowed.saturating_sub(days_used),
+                days_used.saturating_sub(allowed),
+            ),
+            None => (self.limit(), 0),
+        };
+        if let Some(max_stay) = self.max_stay {
+            let stay_days = current_stay(stays, end_date).map_or(0, |di| di.abs_num_days());
+            days_remaining = days_remaining.min(max_stay.saturating_sub(stay_days));
+            days_over = days_over.max(stay_days.saturating_sub(max_stay));
+        }
+        let verdict = if days_over > 0 {
+            Verdict::Exceeds
+        } else {
+            Verdict::Within
+        };
+        Ok(WindowCheck {
+            control_period,
+            intervals,
+            days_used,
+            days_remaining,
+            days_over,
+            verdict,
+        })
+    }
+
+    /// Checks whether a stay in the zone on `date` is allowed in addition to `stays`.
+    pub fn is_allowed_on(&self, stays: &DateIntervalVec, date: NaiveDate) -> Result<bool> {
+        let stays = stays.with(DateInterval::new(date, date)?);
+        Ok(self.check(&stays, date)?.verdict == Verdict::Within)
+    }
+
+    /// Finds the earliest date on or after `from` on which entering the zone is allowed, sliding
+    /// the control period forward day by day.
+    pub fn earliest_entry(&self, stays: &DateIntervalVec, from: NaiveDate) -> Result<NaiveDate> {
+        if self.limit() == 0 {
+            anyhow::bail!("No days are allowed in the control period");
+        }
+        self.find_date(from, |date| self.is_allowed_on(stays, date))?
+            .with_context(|| format!("No entry date found within the control period after {from}"))
+    }
+
+    /// Finds the first date on or after `from` for which `found` is true, sliding the control
+    /// period forward day by day. Once a whole control period has passed, all past stays are out of
+    /// the window, so later dates are not searched.
+    pub(crate) fn find_date(
+        &self,
+        from: NaiveDate,
+        mut found: impl FnMut(NaiveDate) -> Result<bool>,
+    ) -> Result<Option<NaiveDate>> {
+        let mut date = from;
+        for _ in 0..=self.period {
+            if found(date)? {
+                return Ok(Some(date));
+            }
+            date = date + Days::new(1);
+        }
+        Ok(None)
+    }
+
+    /// Calculates how many consecutive days can be spent in the zone when entering on `entry`.
+    /// Returns 0 if entering on that date is not allowed. The result is capped at the allowance and
+    /// the maximum stay.
+    pub fn max_stay(&self, stays: &DateIntervalVec, entry: NaiveDate) -> Result<usize> {
+        let mut num_days = 0;
+        let mut exit = entry;
+        let limit = self.limit();
+        // Every day of the stay is checked against the control period ending on that day.
+        while num_days < limit {
+            let stays = stays.with(DateInterval::new(entry, exit)?);
+            if self.check(&stays, exit)?.verdict == Verdict::Exceeds {
+                break;
+            }
+            num_days += 1;
+            exit = exit + Days::new(1);
+        }
+        Ok(num_days)
+    }
+
+    /// Last day of the longest allowed stay when entering on `entry`. Returns `None` if entering on
+    /// that date is not allowed.
+    pub fn last_day(&self, stays: &DateIntervalVec, entry: NaiveDate) -> Result<Option<NaiveDate>> {
+        let max_stay = self.max_stay(stays, entry)?;
+        Ok(max_stay
+            .checked_sub(1)
+            .map(|days| entry + Days::new(days as u64)))
+    }
+}
+
+/// Stay from its entry until `date`, if `date` is spent in the zone. When a stay ends on the day
+/// another one starts, the later stay is the current one.
+fn current_stay(stays: &DateIntervalVec, date: NaiveDate) -> Option<DateInterval> {
+    stays
+        .as_slice()
+        .iter()
+        .filter(|di| di.contains(date))
+        .max_by_key(|di| di.start())
+        .and_then(|di| DateInterval::new(di.start(), date).ok())
+}
@@ -0,0 +1,206 @@
+//! Month-grid calendar of the days spent in the zone.
+
+use crate::{DateInterval, DateIntervalVec};
+use chrono::{Datelike, Days, Months, NaiveDate};
+use serde::Serialize;
+use std::fmt::Write;
+
+/// How a day is spent with respect to the zone and the control period.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DayState {
+    /// Outside the zone.
+    Outside,
+    /// In the zone, outside the control period.
+    InZone,
+    /// In the zone and counted in the control period.
+    Counted,
+}
+
+impl DayState {
+    pub fn of(stays: &DateIntervalVec, control_period: DateInterval, date: NaiveDate) -> Self {
+        match (stays.contains(date), control_period.contains(date)) {
+            (false, _) => Self::Outside,
+            (true, false) => Self::InZone,
+            (true, true) => Self::Counted,
+        }
+    }
+
+    /// Marker shown next to the day number without colour.
+    fn marker(&self) -> char {
+        match self {
+            Self::Outside => ' ',
+            Self::InZone => '+',
+            Self::Counted => '#',
+        }
+    }
+
+    /// ANSI SGR parameters of the day.
+    fn color(&self) -> &'static str {
+        match self {
+            Self::Outside => "",
+            Self::InZone => "43;30",
+            Self::Counted => "41;97",
+        }
+    }
+}
+
+/// State of a day shown in the calendar.
+#[derive(Debug, Clone, Serialize)]
+pub struct CalendarDay {
+    pub date: NaiveDate,
+    pub state: DayState,
+}
+
+/// Month-grid calendar.
+#[derive(Debug, Clone)]
+pub struct Heatmap {
+    /// Months to show, starting with the month of this date.
+    pub from: NaiveDate,
+    pub num_months: u32,
+    /// Months per row of the calendar.
+    pub columns: usize,
+    /// Use ANSI colours instead of markers.
+    pub color: bool,
+}
+
+/// Width of a month in characters.
+const MONTH_WIDTH: usize = 7 * 3 + 6;
+
+impl Heatmap {
+    /// Months shown in the calendar, as their first days.
+    fn months(&self) -> impl Iterator<Item = NaiveDate> {
+        let first = self.from.with_day(1).expect("first day of month");
+        (0..self.num_months).filter_map(move |n| first.checked_add_months(Months::new(n)))
+    }
+
+    /// States of all days shown in the calendar.
+    pub fn days(&self, stays: &DateIntervalVec, control_period: DateInterval) -> Vec<CalendarDay> {
+        self.months()
+            .flat_map(|first| {
+                first
+                    .iter_days()
+                    .take_while(move |d| d.month() == first.month())
+            })
+            .map(|date| CalendarDay {
+                date,
+                state: DayState::of(stays, control_period, date),
+            })
+            .collect()
+    }
+
+    /// Renders the calendar, highlighting the end of the control period.
+    pub fn render(&self, stays: &DateIntervalVec, control_period: DateInterval) -> String {
+        let months: Vec<_> = self
+            .months()
+            .map(|month| self.render_month(month, stays, control_period))
+            .collect();
+
+        let mut s = String::new();
+        for row in months.chunks(self.columns.max(1)) {
+            let height = row.iter().map(Vec::len).max().unwrap_or_default();
+            for i in 0..height {
+                let line: Vec<_> = row
+                    .iter()
+                    .map(|month| {
+                        month
+                            .get(i)
+                            .cloned()
+                            .unwrap_or_else(|| " ".repeat(MONTH_WIDTH))
+                    })
+                    .collect();
+                writeln!(s, "{}", line.join("   ").trim_end()).expect("write to string");
+            }
+            s.push('\n');
+        }
+        s.push_str(&self.legend(control_period));
+        s
+    }
+
+    /// Renders a month as lines of equal visible width.
+    fn render_month(
+        &self,
+        first: NaiveDate,
+        stays: &DateIntervalVec,
+        control_period: DateInterval,
+    ) -> Vec<String> {
+        let title = first.format("%B %Y").to_string();
+        let mut lines = vec![
+            format!("{title:^MONTH_WIDTH$}"),
+            format!("{:<MONTH_WIDTH$}", " Mo  Tu  We  Th  Fr  Sa  Su"),
+        ];
+        let mut line = "    ".repeat(first.weekday().num_days_from_monday() as usize);
+        let mut date = first;
+        while date.month() == first.month() {
+            let state = DayState::of(stays, control_period, date);
+            let is_end = date == control_period.end();
+            line.push_str(&self.cell(date.day(), state, is_end));
+            if date.weekday().num_days_from_monday() == 6 {
+                lines.push(line.trim_end_matches(' ').to_string());
+                line = String::new();
+            } else {
+                line.push(' ');
+            }
+            date = date + Days::new(1);
+        }
+        if !line.is_empty() {
+            lines.push(line);
+        }
+        // Pad by the visible width, which does not include the colour escape sequences.
+        lines
+            .into_iter()
+            .map(|l| {
+                let visible = visible_width(&l);
+                format!("{l}{}", " ".repeat(MONTH_WIDTH.saturating_sub(visible)))
+            })
+            .collect()
+    }
+
+    fn cell(&self, day: u32, state: DayState, is_end: bool) -> String {
+        if self.color {
+            let mut sgr = state.color().to_string();
+            if is_end {
+                sgr.push_str(if sgr.is_empty() { "1;4" } else { ";1;4" });
+            }
+            if sgr.is_empty() {
+                format!("{day:>3}")
+            } else {
+                format!("\x1b[{sgr}m{day:>3}\x1b[0m")
+            }
+        } else {
+            let marker = if is_end { '<' } else { state.marker() };
+            format!("{day:>2}{marker}")
+        }
+    }
+
+    fn legend(&self, control_period: DateInterval) -> String {
+        let end = control_period.end();
+        if self.color {
+            format!(
+                "\x1b[41;97m   \x1b[0m counted in the control period {control_period}   \
+                 \x1b[43;30m   \x1b[0m in the zone   \x1b[1;4m{}\x1b[0m {end}\n",
+                end.day()
+            )
+        } else {
+            format!(
+                "# counted in the control period {control_period}   + in the zone   \
+                 < {end}\n"
+            )
+        }
+    }
+}
+
+/// Number of visible characters, skipping ANSI escape sequences.
+fn visible_width(s: &str) -> usize {
+    let mut width = 0;
+    let mut in_escape = false;
+    for c in s.chars() {
+        match (in_escape, c) {
+            (false, '\x1b') => in_escape = true,
+            (false, _) => width += 1,
+            (true, 'm') => in_escape = false,
+            (true, _) => {}
+        }
+    }
+    width
+}
@@ -0,0 +1,348 @@
+//! Import of trips from iCalendar (.ics) files and export of milestones to them.
+
+use crate::{Milestone, MilestoneKind, Trip, TripInfo};
+use anyhow::{Context, Result};
+use chrono::{Days, NaiveDate, NaiveDateTime, NaiveTime, TimeDelta};
+use std::io::{BufRead, Write};
+
+/// Selects the calendar events that are stays in the zone. An event matches if it matches every
+/// given criterion. Matching ignores case.
+#[derive(Debug, Clone, Default)]
+pub struct EventFilter {
+    /// One of the event categories.
+    pub category: Option<String>,
+    /// Text contained in the summary or the description of the event.
+    pub keyword: Option<String>,
+    /// Text contained in the location of the event.
+    pub location: Option<String>,
+}
+
+/// Trips read from a calendar and warnings about the events they were made from.
+#[derive(Debug, Clone, Default)]
+pub struct IcsImport {
+    pub trips: Vec<Trip>,
+    pub warnings: Vec<String>,
+}
+
+#[derive(Debug, Default)]
+struct Event {
+    line: usize,
+    start: Option<String>,
+    end: Option<String>,
+    duration: Option<String>,
+    summary: Option<String>,
+    description: Option<String>,
+    location: Option<String>,
+    categories: Vec<String>,
+}
+
+impl EventFilter {
+    fn matches(&self, event: &Event) -> bool {
+        let contains = |text: &Option<String>, pattern: &str| {
+            text.as_ref()
+                .is_some_and(|t| t.to_lowercase().contains(&pattern.to_lowercase()))
+        };
+        self.category.as_ref().is_none_or(|category| {
+            event
+                .categories
+                .iter()
+                .any(|c| c.eq_ignore_ascii_case(category))
+        }) && self.keyword.as_ref().is_none_or(|keyword| {
+            contains(&event.summary, keyword) || contains(&event.description, keyword)
+        }) && self
+            .location
+            .as_ref()
+            .is_none_or(|location| contains(&event.location, location))
+    }
+}
+
+/// Reads the events matching `filter` as trips.
+///
+/// The end date of an all-day event is exclusive, so an all-day event from 1 to 3 March is a trip
+/// from 1 to 2 March. An event without an end date ends after its DURATION, or lasts one day if it
+/// has none. Timed events cover the days of their start and end times. Overlapping events are
+/// merged into one trip with a warning.
+pub fn parse_ics_trips<R: BufRead>(reader: R, filter: &EventFilter) -> Result<IcsImport> {
+    let mut import = IcsImport::default();
+    let mut trips = Vec::new();
+    for event in parse_events(reader)? {
+        if !filter.matches(&event) {
+            continue;
+        }
+        let line = event.line;
+        let trip = event_trip(&event, &mut import.warnings)
+            .with_context(|| format!("Event on line {line}"))?;
+        let info = TripInfo {
+            label: event.summary,
+            notes: event.location,
+            ..TripInfo::default()
+        };
+        trips.push(trip.with_info(info).at_line(line));
+    }
+
+    trips.sort_by_key(|trip| (trip.entry, trip.exit));
+    for trip in trips {
+        match import.trips.last_mut() {
+            Some(last) if trip.entry < last.exit.expect("event trips are closed") => {
+                import
+                    .warnings
+                    .push(format!("The {trip} overlaps the {last}, merged"));
+                last.exit = last.exit.max(trip.exit);
+            }
+            _ => import.trips.push(trip),
+        }
+    }
+    Ok(import)
+}
+
+fn event_trip(event: &Event, warnings: &mut Vec<String>) -> Result<Trip> {
+    let start = event.start.as_ref().context("No DTSTART")?;
+    let (entry, all_day) = parse_date_time(start)?;
+    let end = match (&event.end, &event.duration) {
+        (Some(end), _) => {
+            let (end_date, end_all_day) = parse_date_time(end)?;
+            if all_day != end_all_day {
+                anyhow::bail!("DTSTART and DTEND have different value types");
+            }
+            let midnight = end.ends_with("T000000") || end.ends_with("T000000Z");
+            Some((end_date, midnight))
+        }
+        (None, Some(duration)) => {
+            let duration = parse_duration(duration)?;
+            let end = if all_day {
+                entry.and_time(NaiveTime::MIN)
+            } else {
+                parse_time(start)?
+            } + duration;
+            Some((end.date(), !all_day && end.time() == NaiveTime::MIN))
+        }
+        (None, None) => None,
+    };
+    let exit = match end {
+        None => entry,
+        Some((end_date, midnight)) => {
+            if all_day {
+                // DTEND of an all-day event is the day after the event.
+                if end_date <= entry {
+                    warnings.push(format!(
+                        "The all-day event on line {} ends on its start date, counted as one day",
+                        event.line
+                    ));
+                    entry
+                } else {
+                    end_date - Days::new(1)
+                }
+            } else {
+                if midnight {
+                    warnings.push(format!(
+                        "The event on line {} ends at midnight, {end_date} is counted as a day in \
+                         the zone",
+                        event.line
+                    ));
+                }
+                end_date
+            }
+        }
+    };
+    Trip::new(entry, exit)
+}
+
+/// Parses a DATE or DATE-TIME value. Returns the date and whether it is an all-day date. Times are
+/// taken as the date written in the calendar, without time zone conversion.
+fn parse_date_time(value: &str) -> Result<(NaiveDate, bool)> {
+    let value = value.trim();
+    let date = value
+        .get(..8)
+        .and_then(|date| NaiveDate::parse_from_str(date, "%Y%m%d").ok())
+        .with_context(|| format!("Invalid date '{value}'"))?;
+    Ok((date, !value.contains('T')))
+}
+
+/// Parses a DATE-TIME value, without time zone conversion.
+fn parse_time(value: &str) -> Result<NaiveDateTime> {
+    let value = value.trim();
+    value
+        .get(..15)
+        .and_then(|time| NaiveDateTime::parse_from_str(time, "%Y%m%dT%H%M%S").ok())
+        .with_context(|| format!("Invalid date-time '{value}'"))
+}
+
+/// Parses a DURATION value, such as `P5D`, `P1W` or `PT36H`.
+fn parse_duration(value: &str) -> Result<TimeDelta> {
+    let value = value.trim();
+    let invalid = || anyhow::anyhow!("Invalid duration '{value}'");
+    let rest = value.strip_prefix('+').unwrap_or(value);
+    if rest.starts_with('-') {
+        anyhow::bail!("Negative duration '{value}'");
+    }
+    let rest = rest.strip_prefix('P').ok_or_else(invalid)?;
+    let mut seconds = 0;
+    let mut number = String::new();
+    let mut time = false;
+    for c in rest.chars() {
+        if c.is_ascii_digit() {
+            number.push(c);
+            continue;
+        }
+        if c == 'T' && number.is_empty() && !time {
+            time = true;
+            continue;
+        }
+        let n: i64 = number.parse().map_err(|_| invalid())?;
+        number.clear();
+        seconds += n * match (c, time) {
+            ('W', false) => 7 * 86400,
+            ('D', false) => 86400,
+            ('H', true) => 3600,
+            ('M', true) => 60,
+            ('S', true) => 1,
+            _ => return Err(invalid()),
+        };
+    }
+    if !number.is_empty() || rest.is_empty() {
+        return Err(invalid());
+    }
+    Ok(TimeDelta::seconds(seconds))
+}
+
+/// Reads the VEVENT components, unfolding continued lines. Properties of the components inside an
+/// event, such as alarms, are left out.
+fn parse_events<R: BufRead>(reader: R) -> Result<Vec<Event>> {
+    let mut lines: Vec<(usize, String)> = Vec::new();
+    for (i, line) in reader.lines().enumerate() {
+        let line = line?;
+        let line = line.trim_end_matches('\r');
+        match (line.strip_prefix([' ', '\t']), lines.last_mut()) {
+            (Some(continued), Some((_, last))) => last.push_str(continued),
+            _ => lines.push((i + 1, line.to_string())),
+        }
+    }
+
+    let mut events = Vec::new();
+    let mut event: Option<Event> = None;
+    // Components open inside the event.
+    let mut depth = 0;
+    for (line_num, line) in lines {
+        let Some((name, value)) = line.split_once(':') else {
+            continue;
+        };
+        // Parameters such as VALUE=DATE or TZID are not needed, the value format tells them apart.
+        let name = name.split_once(';').map_or(name, |(name, _)| name);
+        match (name.to_uppercase().as_str(), &mut event) {
+            ("BEGIN", None) if value.eq_ignore_ascii_case("VEVENT") => {
+                event = Some(Event {
+                    line: line_num,
+                    ..Event::default()
+                });
+            }
+            ("BEGIN", Some(_)) => depth += 1,
+            ("END", Some(_)) if depth > 0 => depth -= 1,
+            ("END", Some(_)) if value.eq_ignore_ascii_case("VEVENT") => {
+                events.extend(event.take());
+            }
+            (_, Some(_)) if depth > 0 => {}
+            ("DTSTART", Some(e)) => e.start = Some(value.to_string()),
+            ("DTEND", Some(e)) => e.end = Some(value.to_string()),
+            ("DURATION", Some(e)) => e.duration = Some(value.to_string()),
+            ("SUMMARY", Some(e)) => e.summary = Some(unescape(value)),
+            ("DESCRIPTION", Some(e)) => e.description = Some(unescape(value)),
+            ("LOCATION", Some(e)) => e.location = Some(unescape(value)),
+            ("CATEGORIES", Some(e)) => e
+                .categories
+                .extend(value.split(',').map(|c| unescape(c.trim()))),
+            _ => {}
+        }
+    }
+    Ok(events)
+}
+
+/// Removes the escaping of TEXT values.
+fn unescape(s: &str) -> String {
+    let mut result = String::with_capacity(s.len());
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            result.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') | Some('N') => result.push('\n'),
+            Some(c) => result.push(c),
+            None => result.push('\\'),
+        }
+    }
+    result
+}
+
+/// Writes the milestones as a calendar of all-day events. `stamp` is the creation time of the
+/// calendar, in UTC.
+pub fn write_milestones<W: Write>(
+    mut w: W,
+    milestones: &[Milestone],
+    stamp: NaiveDateTime,
+) -> Result<()> {
+    let stamp = stamp.format("%Y%m%dT%H%M%SZ");
+    write_line(&mut w, "BEGIN:VCALENDAR")?;
+    write_line(&mut w, "VERSION:2.0")?;
+    write_line(
+        &mut w,
+        concat!(
+            "PRODID:-//multi-visa-calc//",
+            env!("CARGO_PKG_VERSION"),
+            "//EN"
+        ),
+    )?;
+    for milestone in milestones {
+        let date = milestone.date.format("%Y%m%d");
+        let kind = match milestone.kind {
+            MilestoneKind::LeaveBy => "leave-by".to_string(),
+            MilestoneKind::EarliestEntry => "earliest-entry".to_string(),
+            MilestoneKind::Recovery { days } => format!("recovery-{days}"),
+        };
+        write_line(&mut w, "BEGIN:VEVENT")?;
+        write_line(&mut w, &format!("UID:{date}-{kind}@multi-visa-calc"))?;
+        write_line(&mut w, &format!("DTSTAMP:{stamp}"))?;
+        write_line(&mut w, &format!("DTSTART;VALUE=DATE:{date}"))?;
+        let end = (milestone.date + Days::new(1)).format("%Y%m%d");
+        write_line(&mut w, &format!("DTEND;VALUE=DATE:{end}"))?;
+        write_line(&mut w, &format!("SUMMARY:{}", escape(&milestone.summary)))?;
+        write_line(&mut w, "TRANSP:TRANSPARENT")?;
+        write_line(&mut w, "END:VEVENT")?;
+    }
+    write_line(&mut w, "END:VCALENDAR")?;
+    Ok(())
+}
+
+/// Writes a content line, folding it at 75 octets.
+fn write_line<W: Write>(w: &mut W, line: &str) -> Result<()> {
+    let mut rest = line;
+    let mut first = true;
+    while !rest.is_empty() {
+        // Continuation lines start with a space, which counts towards the limit.
+        let mut n = rest.len().min(if first { 75 } else { 74 });
+        while !rest.is_char_boundary(n) {
+            n -= 1;
+        }
+        let (chunk, tail) = rest.split_at(n);
+        write!(w, "{}{chunk}\r\n", if first { "" } else { " " })?;
+        rest = tail;
+        first = false;
+    }
+    Ok(())
+}
+
+/// Escapes a TEXT value.
+fn escape(s: &str) -> String {
+    let mut result = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '\\' | ';' | ',' => {
+                result.push('\\');
+                result.push(c);
+            }
+            '\n' => result.push_str("\\n"),
+            _ => result.push(c),
+        }
+    }
+    result
+}
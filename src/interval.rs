@@ -0,0 +1,190 @@
+//! Date intervals and lists of them.
+
+use crate::Trip;
+use anyhow::Result;
+use chrono::{Days, NaiveDate};
+use itertools::Itertools;
+use serde::ser::{SerializeStruct, Serializer};
+use serde::Serialize;
+use std::fmt;
+
+/// Closed interval of dates, both ends included.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct DateInterval {
+    a: NaiveDate,
+    b: NaiveDate,
+}
+
+impl fmt::Display for DateInterval {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "from {} to {}", self.a, self.b)
+    }
+}
+
+impl Serialize for DateInterval {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("DateInterval", 3)?;
+        state.serialize_field("start", &self.a)?;
+        state.serialize_field("end", &self.b)?;
+        state.serialize_field("days", &self.abs_num_days())?;
+        state.end()
+    }
+}
+
+impl DateInterval {
+    /// Makes a new date interval, checking that the interval points are not reversed.
+    pub fn new(a: NaiveDate, b: NaiveDate) -> Result<Self> {
+        if a > b {
+            anyhow::bail!("End date is before the start date");
+        }
+        Ok(Self { a, b })
+    }
+
+    /// First day of the interval.
+    pub fn start(&self) -> NaiveDate {
+        self.a
+    }
+
+    /// Last day of the interval.
+    pub fn end(&self) -> NaiveDate {
+        self.b
+    }
+
+    /// Calculates the number of days in the interval including the last day.
+    pub fn abs_num_days(&self) -> usize {
+        (self.b - self.a).num_days() as usize + 1
+    }
+
+    /// Updates the start date of the interval to the given date `d` if the interval starts before
+    /// `d` and ends on `d` or after.
+    pub fn start_no_earlier(&mut self, d: NaiveDate) {
+        if self.a < d && d <= self.b {
+            self.a = d;
+        }
+    }
+
+    /// Updates the end date of the interval to the given date `d` if the interval starts on `d` or
+    /// before and ends after `d`.
+    pub fn end_no_later(&mut self, d: NaiveDate) {
+        if self.a <= d && d < self.b {
+            self.b = d;
+        }
+    }
+
+    /// Checks whether `d` is in the interval.
+    pub fn contains(&self, d: NaiveDate) -> bool {
+        self.a <= d && d <= self.b
+    }
+
+    /// Checks whether the two intervals have at least one day in common.
+    pub fn overlaps(&self, di: DateInterval) -> bool {
+        self.a <= di.b && di.a <= self.b
+    }
+
+    /// Days at the end of which the midnight is spent in the interval, that is all days except the
+    /// last one. `None` if the interval is a single day.
+    pub fn midnights(&self) -> Option<Self> {
+        (self.a < self.b).then(|| Self {
+            a: self.a,
+            b: self.b - Days::new(1),
+        })
+    }
+}
+
+/// List of date intervals, for example the stays in the visa zone.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct DateIntervalVec(Vec<DateInterval>);
+
+impl DateIntervalVec {
+    /// Makes the list of dates spent on the given trips. Open trips are counted until `until`.
+    pub fn from_trips(trips: &[Trip], until: NaiveDate) -> Self {
+        Self(
+            trips
+                .iter()
+                .filter_map(|trip| trip.interval(until))
+                .collect(),
+        )
+    }
+
+    /// Returns the intervals overlapping `control_period`, clipped to it.
+    pub fn clip(&self, control_period: DateInterval) -> Self {
+        let mut date_intervals = Vec::new();
+        for &di in &self.0 {
+            let mut di = di;
+            if di.overlaps(control_period) {
+                di.start_no_earlier(control_period.a);
+                di.end_no_later(control_period.b);
+                date_intervals.push(di);
+            }
+        }
+        Self(date_intervals)
+    }
+
+    /// Returns a copy of the list with `di` added.
+    pub fn with(&self, di: DateInterval) -> Self {
+        let mut date_intervals = self.0.clone();
+        date_intervals.push(di);
+        Self(date_intervals)
+    }
+
+    /// Checks whether `d` is in any of the intervals.
+    pub fn contains(&self, d: NaiveDate) -> bool {
+        self.0.iter().any(|di| di.contains(d))
+    }
+
+    /// Total number of days in all intervals. A day shared by several intervals, such as the day of
+    /// leaving and entering again, is counted once.
+    pub fn num_spent_days(&self) -> usize {
+        let mut spent_days = 0;
+        let mut last_counted: Option<NaiveDate> = None;
+        for di in self.0.iter().sorted_by_key(|di| di.a) {
+            let mut di = *di;
+            if let Some(d) = last_counted {
+                if d >= di.b {
+                    continue;
+                }
+                di.start_no_earlier(d + Days::new(1));
+            }
+            spent_days += di.abs_num_days();
+            last_counted = Some(di.b);
+        }
+        spent_days
+    }
+
+    /// Midnight-based counterpart of the list: the days ending with a midnight in one of the
+    /// intervals. Its number of spent days is the number of midnights spent, with a day of leaving
+    /// and entering again counted once.
+    pub fn midnights(&self) -> Self {
+        Self(self.0.iter().filter_map(DateInterval::midnights).collect())
+    }
+
+    pub fn as_slice(&self) -> &[DateInterval] {
+        &self.0
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl From<Vec<DateInterval>> for DateIntervalVec {
+    fn from(date_intervals: Vec<DateInterval>) -> Self {
+        Self(date_intervals)
+    }
+}
+
+impl fmt::Display for DateIntervalVec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut n = 1;
+        let mut iter = self.0.iter().peekable();
+        while let Some(di) = iter.next() {
+            write!(
+                f,
+                "{n}) {di}{}",
+                if iter.peek().is_some() { ", " } else { "" }
+            )?;
+            n += 1;
+        }
+        Ok(())
+    }
+}
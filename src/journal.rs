@@ -0,0 +1,207 @@
+//! Trip journals in TOML format with several travellers.
+//!
+//! ```toml
+//! [[traveller]]
+//! name = "Alice"
+//! nationality = "GB"
+//! regimes = ["schengen"]
+//!
+//! [[traveller.trip]]
+//! entry = 2024-01-05
+//! exit = 2024-01-20
+//! country = "FR"
+//! label = "Paris office"
+//!
+//! [[traveller.trip]]
+//! entry = 2024-03-01
+//! exit = 2024-03-12
+//!
+//! [[traveller.trip.leg]]
+//! date = 2024-03-01
+//! country = "DE"
+//!
+//! [[traveller.trip.leg]]
+//! date = 2024-03-05
+//! country = "GB"
+//! border = "air"
+//!
+//! [[traveller.permit]]
+//! kind = "residence-permit"
+//! country = "DE"
+//! start = 2024-06-01
+//! end = 2025-05-31
+//! ```
+
+use crate::bilateral::Agreement;
+use crate::{parse_date, Border, DateInterval, Leg, Permit, PermitKind, Trip, TripInfo};
+use anyhow::{Context, Result};
+use chrono::NaiveDate;
+use serde::{Deserialize, Deserializer};
+
+/// Journal of trips of several travellers.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Journal {
+    #[serde(rename = "traveller", default)]
+    pub travellers: Vec<Traveller>,
+    /// Bilateral agreements in addition to the built-in ones.
+    #[serde(rename = "agreement", default)]
+    pub agreements: Vec<Agreement>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Traveller {
+    pub name: String,
+    pub nationality: Option<String>,
+    /// Names of the visa regimes that apply to the traveller.
+    #[serde(default)]
+    pub regimes: Vec<String>,
+    #[serde(rename = "trip", default)]
+    pub trips: Vec<JournalTrip>,
+    #[serde(rename = "permit", default)]
+    pub permits: Vec<JournalPermit>,
+}
+
+/// Trip as written in the journal. An omitted exit date means a trip that has not ended yet.
+#[derive(Debug, Clone, Deserialize)]
+pub struct JournalTrip {
+    #[serde(deserialize_with = "deserialize_date")]
+    pub entry: NaiveDate,
+    #[serde(default, deserialize_with = "deserialize_opt_date")]
+    pub exit: Option<NaiveDate>,
+    #[serde(rename = "leg", default)]
+    pub legs: Vec<JournalLeg>,
+    #[serde(flatten)]
+    pub info: TripInfo,
+}
+
+/// Long-stay visa or residence permit in the journal.
+#[derive(Debug, Clone, Deserialize)]
+pub struct JournalPermit {
+    pub kind: PermitKind,
+    pub country: Option<String>,
+    #[serde(deserialize_with = "deserialize_date")]
+    pub start: NaiveDate,
+    #[serde(deserialize_with = "deserialize_date")]
+    pub end: NaiveDate,
+}
+
+/// Leg of a trip in the journal.
+#[derive(Debug, Clone, Deserialize)]
+pub struct JournalLeg {
+    #[serde(deserialize_with = "deserialize_date")]
+    pub date: NaiveDate,
+    pub country: String,
+    pub border: Option<Border>,
+}
+
+impl Journal {
+    pub fn parse(s: &str) -> Result<Self> {
+        Ok(toml::from_str(s)?)
+    }
+
+    /// Finds a traveller by name, ignoring case. Without a name, the only traveller in the journal
+    /// is selected.
+    pub fn traveller(&self, name: Option<&str>) -> Result<&Traveller> {
+        match name {
+            Some(name) => self
+                .travellers
+                .iter()
+                .find(|t| t.name.eq_ignore_ascii_case(name))
+                .with_context(|| {
+                    format!(
+                        "Traveller '{name}' not found, choose one of: {}",
+                        self.names()
+                    )
+                }),
+            None if self.travellers.len() == 1 => Ok(&self.travellers[0]),
+            None if self.travellers.is_empty() => anyhow::bail!("No travellers in the journal"),
+            None => anyhow::bail!("Choose a traveller: {}", self.names()),
+        }
+    }
+
+    fn names(&self) -> String {
+        let names: Vec<_> = self.travellers.iter().map(|t| t.name.as_str()).collect();
+        names.join(", ")
+    }
+}
+
+impl Traveller {
+    /// Converts the journal entries to trips, numbered from 1 in the order they are written.
+    pub fn trips(&self) -> Result<Vec<Trip>> {
+        self.trips
+            .iter()
+            .enumerate()
+            .map(|(i, t)| {
+                let context = || format!("Traveller {}, trip {}", self.name, i + 1);
+                let trip = if let Some(exit) = t.exit {
+                    Trip::new(t.entry, exit).with_context(context)?
+                } else {
+                    Trip::open(t.entry)
+                };
+                let legs = t
+                    .legs
+                    .iter()
+                    .map(|leg| Leg {
+                        date: leg.date,
+                        country: leg.country.clone(),
+                        border: leg.border,
+                    })
+                    .collect();
+                trip.with_info(t.info.clone())
+                    .with_legs(legs)
+                    .with_context(context)
+            })
+            .collect()
+    }
+
+    /// Converts the permits in the journal, numbered from 1 in the order they are written.
+    pub fn permits(&self) -> Result<Vec<Permit>> {
+        self.permits
+            .iter()
+            .enumerate()
+            .map(|(i, p)| {
+                Ok(Permit {
+                    kind: p.kind,
+                    country: p.country.clone(),
+                    period: DateInterval::new(p.start, p.end)
+                        .with_context(|| format!("Traveller {}, permit {}", self.name, i + 1))?,
+                })
+            })
+            .collect()
+    }
+}
+
+/// Date written either as a TOML local date or as a `YYYY-MM-DD` string.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum DateValue {
+    Toml(toml::value::Datetime),
+    Text(String),
+}
+
+impl DateValue {
+    fn into_date(self) -> Result<NaiveDate> {
+        match self {
+            Self::Toml(toml::value::Datetime {
+                date: Some(date),
+                time: None,
+                offset: None,
+            }) => NaiveDate::from_ymd_opt(date.year.into(), date.month.into(), date.day.into())
+                .with_context(|| format!("Invalid date {date}")),
+            Self::Toml(datetime) => anyhow::bail!("Expected a date without time, got {datetime}"),
+            Self::Text(s) => parse_date(&s),
+        }
+    }
+}
+
+fn deserialize_date<'de, D: Deserializer<'de>>(deserializer: D) -> Result<NaiveDate, D::Error> {
+    DateValue::deserialize(deserializer)?
+        .into_date()
+        .map_err(serde::de::Error::custom)
+}
+
+fn deserialize_opt_date<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<NaiveDate>, D::Error> {
+    deserialize_date(deserializer).map(Some)
+}
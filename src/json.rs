@@ -0,0 +1,27 @@
+//! Versioned JSON output.
+
+use serde::Serialize;
+
+/// Version of the JSON output schema. Incremented on incompatible changes.
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// Envelope of every JSON document: the schema version, the command that produced the result and
+/// any warnings raised while loading the input.
+#[derive(Debug, Serialize)]
+pub struct Report<'a, T> {
+    pub schema_version: u32,
+    pub command: &'a str,
+    pub warnings: &'a [String],
+    pub result: &'a T,
+}
+
+impl<'a, T: Serialize> Report<'a, T> {
+    pub fn new(command: &'a str, warnings: &'a [String], result: &'a T) -> Self {
+        Self {
+            schema_version: SCHEMA_VERSION,
+            command,
+            warnings,
+            result,
+        }
+    }
+}
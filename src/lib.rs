@@ -0,0 +1,68 @@
+//! Multiple-entry visa calculator library
+//!
+//! The rolling-window engine behind the `multi-visa-calc` binary. It calculates the Schengen visa
+//! allowance (which applies to UK citizens too) according to the "90 out of 180 days" rule and can
+//! be embedded in other tools.
+//!
+//! ```
+//! use chrono::NaiveDate;
+//! use multi_visa_calc::{DateIntervalVec, Engine, Trip, Verdict};
+//!
+//! let d = |s| NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap();
+//! let trips = [Trip::new(d("2024-01-01"), d("2024-01-10")).unwrap()];
+//! let stays = DateIntervalVec::from_trips(&trips, d("2024-02-01"));
+//! let check = Engine::default().check(&stays, d("2024-02-01")).unwrap();
+//! assert_eq!(check.days_used, 10);
+//! assert_eq!(check.verdict, Verdict::Within);
+//! ```
+
+pub mod bilateral;
+pub mod csv_log;
+pub mod engine;
+pub mod heatmap;
+pub mod ics;
+pub mod interval;
+pub mod journal;
+pub mod json;
+pub mod membership;
+pub mod milestones;
+pub mod optimize;
+pub mod parse;
+pub mod permit;
+pub mod plan;
+pub mod regime;
+pub mod report;
+pub mod spt;
+pub mod srt;
+pub mod tax;
+pub mod team;
+pub mod timeline;
+pub mod trip;
+
+pub use csv_log::{parse_csv_trips, ColumnMap};
+pub use engine::{Engine, Verdict, WindowCheck};
+pub use heatmap::{CalendarDay, DayState, Heatmap};
+pub use ics::{parse_ics_trips, EventFilter};
+pub use interval::{DateInterval, DateIntervalVec};
+pub use journal::{Journal, Traveller};
+pub use membership::Membership;
+pub use milestones::{Milestone, MilestoneKind};
+pub use optimize::Constraints;
+pub use parse::{parse_date, parse_interval, parse_trips};
+pub use permit::{Exclusion, Permit, PermitKind};
+pub use plan::{Breach, PlannedTrip};
+pub use regime::{Regime, RegimeUsage};
+pub use report::TravelReport;
+pub use spt::PresenceTest;
+pub use srt::{Declaration, ResidenceTest, Tie};
+pub use tax::{Convention, TaxRule, TaxYearDays, YearStart};
+pub use team::{SortKey, TravellerStatus};
+pub use timeline::TimelineDay;
+pub use trip::{open_trip, sort_trips, Border, Leg, Trip, TripInfo};
+
+/// Date format used for input and output.
+pub const DATE_FMT: &str = "%Y-%m-%d";
+/// Default number of days in the visa control period.
+pub const CONTROL_PERIOD_DAYS: usize = 180;
+/// Default maximum number of days allowed in the control period.
+pub const ALLOWED_DAYS: usize = 90;
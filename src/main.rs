@@ -1,199 +1,1526 @@
 //! Multiple-entry visa calculator
 //!
 //! This program can be used to calculate the Schengen visa allowance (which applies to UK citizens
-//! too) and plan trips according to the "90 out of 180 days" rule.
+//! too) and plan trips according to the "90 out of 180 days" rule, or according to the rules of
+//! other visa regimes.
 
-use anyhow::Result;
-use chrono::{Datelike, Days, NaiveDate, Utc};
-use clap::Parser;
+use anyhow::{Context, Result};
+use chrono::{Datelike, Days, Months, NaiveDate, Utc};
+use clap::{Args, Parser, Subcommand, ValueEnum};
 use itertools::Itertools;
-use std::fmt;
-use std::fs::OpenOptions;
-use std::io::{self, BufRead, BufReader};
+use multi_visa_calc::bilateral::{self, Agreement, BilateralCheck};
+use multi_visa_calc::json::Report;
+use multi_visa_calc::{
+    ics, membership, open_trip, parse_csv_trips, parse_date, parse_ics_trips, parse_interval,
+    parse_trips, permit, regime, report, sort_trips, spt, srt, tax, team, timeline, CalendarDay,
+    ColumnMap, Constraints, Convention, DateInterval, DateIntervalVec, Engine, EventFilter,
+    Exclusion, Heatmap, Journal, Milestone, Permit, PlannedTrip, PresenceTest, Regime, RegimeUsage,
+    ResidenceTest, SortKey, TaxRule, TaxYearDays, Tie, TimelineDay, TravelReport, Traveller,
+    TravellerStatus, Trip, Verdict, WindowCheck, YearStart,
+};
+use serde::Serialize;
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufRead, BufReader, BufWriter, IsTerminal, Read};
+use std::process::ExitCode;
+use std::slice;
 
-const DATE_FMT: &str = "%Y-%m-%d";
-const CONTROL_PERIOD_DAYS: usize = 180;
-const ALLOWED_DAYS: usize = 90;
+/// Exit code when the allowance is exceeded or the stay is not allowed.
+const EXIT_NOT_ALLOWED: u8 = 1;
+/// Exit code on errors.
+const EXIT_ERROR: u8 = 2;
 
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about = None)]
 struct Cli {
-    /// End date for the visa calculation. Defaults to today's date.
-    #[arg(short, long)]
-    end: Option<String>,
+    #[command(flatten)]
+    trips: TripArgs,
 
-    /// File with dates in YYYY-MM-DD format. Dates should contain both the entry and exit dates for
-    /// each interval.
-    #[arg(short, long)]
-    file: Option<String>,
+    /// Output format.
+    #[arg(long, global = true, value_enum, default_value_t = Format::Text)]
+    format: Format,
+
+    #[command(subcommand)]
+    command: Command,
+}
 
-    /// Number of days in the visa control period.
-    #[arg(short, long, default_value_t = CONTROL_PERIOD_DAYS)]
-    period: usize,
+#[derive(Copy, Clone, Debug, ValueEnum)]
+enum Format {
+    /// Human-readable text.
+    Text,
+    /// JSON document with a versioned schema.
+    Json,
+}
 
-    /// Maximum number of days allowed.
-    #[arg(short, long, default_value_t = ALLOWED_DAYS)]
-    allowed: usize,
+#[derive(Copy, Clone, Debug, PartialEq, Eq, ValueEnum)]
+enum InputFormat {
+    /// Detect the format from the file name.
+    Auto,
+    /// Entry and exit dates, one trip per line.
+    Lines,
+    /// CSV trip log with one trip per row.
+    Csv,
+    /// TOML journal with trips of several travellers.
+    Journal,
+    /// iCalendar file with trips as events.
+    Ics,
 }
 
-#[derive(Debug, Copy, Clone)]
-struct DateInterval {
-    a: NaiveDate,
-    b: NaiveDate,
+/// Options for loading trips, shared by all commands.
+#[derive(Args, Debug)]
+struct TripArgs {
+    /// File with trips, one per line, as entry and exit dates in YYYY-MM-DD format. Entry and exit
+    /// dates can also be given on separate lines. A single entry date on the last line means being
+    /// in the zone since that date. Defaults to the standard input.
+    #[arg(short, long, global = true)]
+    file: Option<String>,
+
+    /// Format of the trips file. By default, files ending in .csv are read as CSV trip logs, files
+    /// ending in .toml as journals and files ending in .ics as calendars.
+    #[arg(long, global = true, value_enum, default_value_t = InputFormat::Auto)]
+    input_format: InputFormat,
+
+    /// Mapping of trip fields to CSV columns by header name or position from 1, for example
+    /// entry=Arrived,exit=Departed,country=3. Fields: entry, exit, label, country, purpose, notes,
+    /// border.
+    #[arg(long, global = true, default_value = "")]
+    columns: ColumnMap,
+
+    /// Name of the traveller in the journal. Can be omitted if the journal has one traveller.
+    #[arg(short, long, global = true)]
+    traveller: Option<String>,
+
+    /// Only read calendar events with this category as trips.
+    #[arg(long, global = true)]
+    ics_category: Option<String>,
+
+    /// Only read calendar events with this text in the summary or description as trips.
+    #[arg(long, global = true)]
+    ics_keyword: Option<String>,
+
+    /// Only read calendar events with this text in the location as trips.
+    #[arg(long, global = true)]
+    ics_location: Option<String>,
+
+    /// Nationality of the traveller as a country code, for bilateral agreements. Defaults to the
+    /// nationality of the traveller in a journal.
+    #[arg(long, global = true)]
+    nationality: Option<String>,
+
+    /// Bilateral agreement as NATIONALITY:COUNTRY:DAYS, allowing nationals to spend DAYS more in
+    /// COUNTRY than the regime allows. Can be repeated.
+    #[arg(long, global = true)]
+    agreement: Vec<Agreement>,
+
+    /// Long-stay visa or residence permit as KIND:FROM..TO, where KIND is long-stay-visa, d-visa or
+    /// residence-permit. Days in the zone covered by a permit are not counted. Can be repeated.
+    #[arg(long, global = true)]
+    permit: Vec<Permit>,
+
+    /// Visa regime whose rules apply. See the regimes command for the list.
+    #[arg(short, long, global = true, default_value = "schengen")]
+    regime: Regime,
+
+    /// Number of days in the visa control period. Defaults to the period of the regime.
+    #[arg(short, long, global = true)]
+    period: Option<usize>,
+
+    /// Maximum number of days allowed. Defaults to the allowance of the regime.
+    #[arg(short, long, global = true)]
+    allowed: Option<usize>,
 }
 
-impl fmt::Display for DateInterval {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "from {} to {}", self.a, self.b)
+impl TripArgs {
+    /// Engine for the regime with the period and the allowance overridden by the options.
+    fn engine(&self) -> Engine {
+        let regime = Regime {
+            period: self.period.unwrap_or(self.regime.period),
+            allowed: self.allowed.or(self.regime.allowed),
+            ..self.regime.clone()
+        };
+        regime.engine()
     }
 }
 
-impl DateInterval {
-    /// Makes a new date interval, checking that the interval points are not reversed.
-    pub fn new(a: NaiveDate, b: NaiveDate) -> Result<Self> {
-        if a > b {
-            anyhow::bail!("End date is before the start date");
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Check the days spent in the control period ending on a date. Exits with 1 if the allowance
+    /// is exceeded.
+    Check {
+        /// End date of the control period. Defaults to today's date.
+        #[arg(short, long, value_parser = parse_date)]
+        end: Option<NaiveDate>,
+    },
+    /// Find the earliest date on which entering the zone is allowed.
+    NextEntry {
+        /// Date to search from. Defaults to today's date.
+        #[arg(long, value_parser = parse_date)]
+        from: Option<NaiveDate>,
+    },
+    /// Calculate the maximum length of the next stay, or check planned trips. Exits with 1 if
+    /// entering is not allowed or a planned trip breaks the rule.
+    Plan {
+        /// Planned entry date. Defaults to the earliest allowed entry date from today.
+        #[arg(long, value_parser = parse_date, conflicts_with = "planned")]
+        entry: Option<NaiveDate>,
+
+        /// File with planned trips in the same format as the trips file.
+        #[arg(long)]
+        planned: Option<String>,
+    },
+    /// Print the allowance for every day in a date range.
+    Timeline {
+        /// First day of the range.
+        #[arg(long, value_parser = parse_date)]
+        from: NaiveDate,
+
+        /// Last day of the range. Defaults to today's date.
+        #[arg(long, value_parser = parse_date)]
+        to: Option<NaiveDate>,
+
+        /// Print the timeline as CSV instead of a table in the text format.
+        #[arg(long)]
+        csv: bool,
+    },
+    /// Print a calendar of the days spent in the zone and counted in the control period.
+    Calendar {
+        /// End date of the control period. Defaults to today's date.
+        #[arg(short, long, value_parser = parse_date)]
+        end: Option<NaiveDate>,
+
+        /// A date in the first month to show. Defaults to the start of the control period.
+        #[arg(long, value_parser = parse_date)]
+        from: Option<NaiveDate>,
+
+        /// Number of months to show. Defaults to the months up to the end of the control period.
+        #[arg(long)]
+        months: Option<u32>,
+
+        /// Number of months printed side by side.
+        #[arg(long, default_value_t = 3)]
+        per_row: usize,
+
+        /// When to colour the calendar. Colours are off in auto mode if the output is not a
+        /// terminal or NO_COLOR is set.
+        #[arg(long, value_enum, default_value_t = ColorMode::Auto)]
+        color: ColorMode,
+    },
+    /// Schedule trips that maximise the days spent in the zone.
+    Optimize {
+        /// First day of the schedule. Defaults to today's date.
+        #[arg(long, value_parser = parse_date)]
+        from: Option<NaiveDate>,
+
+        /// Last day of the schedule. Defaults to a year from the first day.
+        #[arg(long, value_parser = parse_date)]
+        to: Option<NaiveDate>,
+
+        /// Date or date range FROM..TO that has to be spent outside the zone. Can be repeated.
+        #[arg(long, value_parser = parse_interval)]
+        outside: Vec<DateInterval>,
+
+        /// Minimum length of a trip in days.
+        #[arg(long, default_value_t = 1)]
+        min_days: usize,
+    },
+    /// List the trips.
+    History,
+    /// Export the last day to leave, the earliest entry date and allowance milestones as an
+    /// iCalendar file.
+    ExportIcs {
+        /// Date to calculate the milestones from. Defaults to today's date.
+        #[arg(long, value_parser = parse_date)]
+        date: Option<NaiveDate>,
+
+        /// Add the date on which this many days of allowance are available. Can be repeated.
+        #[arg(long, default_values_t = [30, 60, 90])]
+        recover: Vec<usize>,
+
+        /// File to write the calendar to. Defaults to the standard output.
+        #[arg(short, long)]
+        output: Option<String>,
+    },
+    /// Write an HTML report with the trip history, the days used over time and planned trips.
+    Report {
+        /// Date of the report. Defaults to today's date.
+        #[arg(long, value_parser = parse_date)]
+        date: Option<NaiveDate>,
+
+        /// File with planned trips in the same format as the trips file.
+        #[arg(long)]
+        planned: Option<String>,
+
+        /// File to write the report to. Defaults to the standard output.
+        #[arg(short, long)]
+        output: Option<String>,
+    },
+    /// List the built-in visa regimes.
+    Regimes,
+    /// Print the days used under each regime. Legs of trips are counted in the regime of their
+    /// country, and a day of travelling between two regimes counts in both. Trips without a country
+    /// are counted under the selected regime only.
+    Usage {
+        /// End date of the control periods. Defaults to today's date.
+        #[arg(short, long, value_parser = parse_date)]
+        end: Option<NaiveDate>,
+
+        /// Regimes to count, separated by commas. Defaults to the regimes of the traveller in a
+        /// journal, or all built-in regimes.
+        #[arg(long, value_delimiter = ',')]
+        regimes: Vec<Regime>,
+    },
+    /// Apply the UK statutory residence test to a tax year, counting the days in the UK at
+    /// midnight.
+    Srt {
+        /// Year in which the tax year starts on 6 April. Defaults to the current tax year.
+        #[arg(long)]
+        year: Option<i32>,
+
+        /// Ties to the UK, separated by commas: family, accommodation, work, 90-day or country. The
+        /// 90-day tie is also found from the trips.
+        #[arg(long, value_delimiter = ',')]
+        ties: Vec<Tie>,
+
+        /// Resident in the UK in one or more of the previous three tax years.
+        #[arg(long)]
+        resident_before: bool,
+
+        /// Working full time abroad.
+        #[arg(long)]
+        works_abroad: bool,
+    },
+    /// Apply the US substantial presence test to a calendar year, and print the usage of the
+    /// regime alongside.
+    Spt {
+        /// Calendar year to test. Defaults to the current year.
+        #[arg(long)]
+        year: Option<i32>,
+
+        /// Date or date range FROM..TO spent in the US as an exempt individual, such as a teacher,
+        /// trainee or student. Can be repeated.
+        #[arg(long, value_parser = parse_interval)]
+        exempt: Vec<DateInterval>,
+    },
+    /// Count the days of presence in a country per tax year, and the day on which the threshold of
+    /// tax residence is reached with the planned trips.
+    TaxDays {
+        /// Country to count the days in, by code or name. Defaults to all trips.
+        #[arg(long)]
+        country: Option<String>,
+
+        /// Month and day on which the tax year starts, as MM-DD.
+        #[arg(long, default_value = "01-01")]
+        year_start: YearStart,
+
+        /// Days counted: any-part of a day, midnights, or any part excluding-transit days.
+        #[arg(long, default_value = "any-part")]
+        convention: Convention,
+
+        /// Days in a tax year that make the traveller resident.
+        #[arg(long, default_value_t = 183)]
+        threshold: usize,
+
+        /// File with planned trips in the same format as the trips file.
+        #[arg(long)]
+        planned: Option<String>,
+
+        /// First tax year to count, by the year it starts in. Defaults to the year of the first
+        /// trip.
+        #[arg(long)]
+        from_year: Option<i32>,
+
+        /// Last tax year to count. Defaults to the current year, or the year of the last planned
+        /// trip if later.
+        #[arg(long)]
+        to_year: Option<i32>,
+    },
+    /// Print the allowance of every traveller in a journal. Exits with 1 if any of the listed
+    /// travellers exceeds the allowance.
+    Team {
+        /// Date of the report. Defaults to today's date.
+        #[arg(long, value_parser = parse_date)]
+        date: Option<NaiveDate>,
+
+        /// Order of the travellers.
+        #[arg(long, value_enum, default_value_t = TeamSort::Name)]
+        sort: TeamSort,
+
+        /// Only list travellers with at most this many days remaining.
+        #[arg(long)]
+        max_remaining: Option<usize>,
+
+        /// Only list travellers exceeding the allowance.
+        #[arg(long)]
+        non_compliant: bool,
+    },
+}
+
+#[derive(Copy, Clone, Debug, ValueEnum)]
+enum TeamSort {
+    /// By name.
+    Name,
+    /// Most days used first.
+    Used,
+    /// Fewest days remaining first.
+    Remaining,
+    /// Latest next entry date first.
+    NextEntry,
+}
+
+#[derive(Copy, Clone, Debug, ValueEnum)]
+enum ColorMode {
+    /// Colour when writing to a terminal.
+    Auto,
+    /// Always colour.
+    Always,
+    /// Mark the days with symbols instead of colours.
+    Never,
+}
+
+impl ColorMode {
+    fn enabled(self) -> bool {
+        match self {
+            Self::Auto => {
+                io::stdout().is_terminal()
+                    && std::env::var_os("NO_COLOR").is_none_or(|v| v.is_empty())
+            }
+            Self::Always => true,
+            Self::Never => false,
         }
-        Ok(Self { a, b })
     }
+}
 
-    /// Calculates the number of days in the interval including the last day.
-    pub fn abs_num_days(&self) -> usize {
-        (self.b - self.a).num_days() as usize + 1
+impl From<TeamSort> for SortKey {
+    fn from(sort: TeamSort) -> Self {
+        match sort {
+            TeamSort::Name => Self::Name,
+            TeamSort::Used => Self::DaysUsed,
+            TeamSort::Remaining => Self::DaysRemaining,
+            TeamSort::NextEntry => Self::EarliestEntry,
+        }
     }
+}
 
-    /// Updates the start date of the interval to the given date `d` if the interval starts before
-    /// `d` and ends on `d` or after.
-    pub fn start_no_earlier(&mut self, d: NaiveDate) {
-        if self.a < d && d <= self.b {
-            self.a = d;
+impl Command {
+    /// Name of the command in the JSON output.
+    fn name(&self) -> &'static str {
+        match self {
+            Self::Check { .. } => "check",
+            Self::NextEntry { .. } => "next-entry",
+            Self::Plan { .. } => "plan",
+            Self::Timeline { .. } => "timeline",
+            Self::Calendar { .. } => "calendar",
+            Self::Optimize { .. } => "optimize",
+            Self::History => "history",
+            Self::ExportIcs { .. } => "export-ics",
+            Self::Report { .. } => "report",
+            Self::Regimes => "regimes",
+            Self::Usage { .. } => "usage",
+            Self::Srt { .. } => "srt",
+            Self::TaxDays { .. } => "tax-days",
+            Self::Spt { .. } => "spt",
+            Self::Team { .. } => "team",
         }
     }
+}
 
-    pub fn end_no_later(&mut self, d: NaiveDate) {
-        if self.a <= d && d < self.b {
-            self.b = d;
+/// Writes command results in the selected format.
+struct Output {
+    format: Format,
+    command: &'static str,
+    warnings: Vec<String>,
+}
+
+impl Output {
+    /// Writes `result` as JSON, or as text using `text`.
+    fn emit<T: Serialize>(&self, result: &T, text: impl FnOnce(&T) -> Result<()>) -> Result<()> {
+        match self.format {
+            Format::Text => {
+                for warning in &self.warnings {
+                    eprintln!("WARNING: {warning}");
+                }
+                text(result)
+            }
+            Format::Json => {
+                let report = Report::new(self.command, &self.warnings, result);
+                serde_json::to_writer_pretty(io::stdout(), &report)?;
+                println!();
+                Ok(())
+            }
         }
     }
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
 
-    pub fn overlaps(&self, di: DateInterval) -> bool {
-        self.a <= di.b && di.a <= self.b
+    match run(cli) {
+        Ok(code) => code,
+        Err(e) => {
+            eprintln!("Error: {e:#}");
+            ExitCode::from(EXIT_ERROR)
+        }
     }
 }
 
-struct DateIntervalVec(Vec<DateInterval>);
+fn run(cli: Cli) -> Result<ExitCode> {
+    let today = today()?;
+    let engine = cli.trips.engine();
+    let mut out = Output {
+        format: cli.format,
+        command: cli.command.name(),
+        warnings: Vec::new(),
+    };
 
-impl DateIntervalVec {
-    fn from_dates(dates: &[NaiveDate], control_period: DateInterval) -> Result<Self> {
-        let mut date_intervals = Vec::new();
-        for (&a, &b) in dates.iter().tuples() {
-            let mut di = DateInterval::new(a, b)?;
-            if di.overlaps(control_period) {
-                di.start_no_earlier(control_period.a);
-                di.end_no_later(control_period.b);
-                date_intervals.push(di);
-            }
+    if let Command::Regimes = cli.command {
+        return regimes(&out);
+    }
+
+    if let Command::Team {
+        date,
+        sort,
+        max_remaining,
+        non_compliant,
+    } = cli.command
+    {
+        if input_format(&cli.trips) != InputFormat::Journal {
+            anyhow::bail!("The team report needs a journal file");
         }
-        Ok(Self(date_intervals))
+        let journal = load_journal(&cli.trips)?;
+        let mut statuses =
+            engine.team_status(&journal, &cli.trips.regime, date.unwrap_or(today))?;
+        team::filter_statuses(&mut statuses, max_remaining, non_compliant);
+        team::sort_statuses(&mut statuses, sort.into());
+        return team_report(&out, statuses);
     }
 
-    fn num_spent_days(&self) -> usize {
-        let spent_days: usize = self.0.iter().map(|di| di.abs_num_days()).sum();
-        spent_days
+    let input = load_input(&cli.trips, &mut out.warnings)?;
+    let all_trips = &input.trips;
+    // Only the trips to the zone while the rules apply are counted, without the days covered by
+    // permits.
+    let regime = &cli.trips.regime;
+    let (trips, excluded) = regime.counted_trips(all_trips, &input.permits);
+    let num_ignored = all_trips
+        .iter()
+        .filter(|trip| regime.applicable_trips(slice::from_ref(trip)).is_empty())
+        .count();
+    // The usage command counts the trips under every regime instead, and the tax residence commands
+    // count the trips to their country.
+    if num_ignored > 0
+        && !matches!(
+            cli.command,
+            Command::Usage { .. }
+                | Command::Srt { .. }
+                | Command::TaxDays { .. }
+                | Command::Spt { .. }
+        )
+    {
+        out.warnings.push(format!(
+            "{num_ignored} trip{} outside the zone of the {} regime not counted",
+            plural(num_ignored),
+            regime.name
+        ));
     }
-}
 
-impl fmt::Display for DateIntervalVec {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let mut n = 1;
-        let mut iter = self.0.iter().peekable();
-        while let Some(di) = iter.next() {
-            write!(
-                f,
-                "{n}) {di}{}",
-                if iter.peek().is_some() { ", " } else { "" }
+    match cli.command {
+        Command::Check { end } => {
+            let end = end.unwrap_or(today);
+            // Only the agreements of the nationality with countries of the regime apply.
+            let bilateral = match input.nationality(&cli.trips) {
+                Some(nationality) => {
+                    let agreements: Vec<_> = input
+                        .agreements
+                        .iter()
+                        .filter(|a| {
+                            a.nationality.eq_ignore_ascii_case(&nationality)
+                                && regime.covers(&a.country)
+                        })
+                        .cloned()
+                        .collect();
+                    if agreements.is_empty() {
+                        None
+                    } else {
+                        engine.bilateral_check(&trips, end, &nationality, &agreements)?
+                    }
+                }
+                None => None,
+            };
+            check(&out, &engine, &trips, excluded, bilateral, end)
+        }
+        Command::NextEntry { from } => next_entry(&out, &engine, &trips, from.unwrap_or(today)),
+        Command::Plan {
+            planned: Some(filename),
+            ..
+        } => {
+            let planned = load_planned(&filename, &trips, today)?;
+            let until = engine.open_trip_until(&trips, &planned, today)?;
+            if let Some(trip) = open_trip(&trips) {
+                out.warnings.push(format!(
+                    "The open trip from {} is counted until {until}, assuming the zone is left \
+                     by then",
+                    trip.entry
+                ));
+            }
+            plan_trips(&out, &engine, &trips, &planned, until)
+        }
+        Command::Plan { entry, .. } => plan(&out, &engine, &trips, today, entry),
+        Command::Timeline { from, to, csv } => {
+            let range = DateInterval::new(from, to.unwrap_or(today))?;
+            timeline(&out, &engine, &trips, range, csv)
+        }
+        Command::Calendar {
+            end,
+            from,
+            months,
+            per_row,
+            color,
+        } => {
+            let end = end.unwrap_or(today);
+            let control_period = engine.control_period(end)?;
+            let from = from.unwrap_or(control_period.start());
+            let num_months = months.unwrap_or_else(|| {
+                let months = |d: NaiveDate| d.year() * 12 + d.month0() as i32;
+                (months(end) - months(from) + 1).max(1) as u32
+            });
+            let heatmap = Heatmap {
+                from,
+                num_months,
+                columns: per_row,
+                color: color.enabled(),
+            };
+            calendar(&out, &trips, &heatmap, control_period)
+        }
+        Command::Optimize {
+            from,
+            to,
+            outside,
+            min_days,
+        } => {
+            let from = from.unwrap_or(today);
+            let to = if let Some(date) = to {
+                date
+            } else {
+                from.checked_add_months(Months::new(12))
+                    .context("Bad horizon")?
+                    - Days::new(1)
+            };
+            let constraints = Constraints {
+                horizon: DateInterval::new(from, to)?,
+                outside,
+                min_trip_days: min_days,
+            };
+            optimize(&out, &engine, &trips, &constraints)
+        }
+        Command::History => history(&out, all_trips, today),
+        Command::ExportIcs {
+            date,
+            recover,
+            output,
+        } => export_ics(
+            &out,
+            &engine,
+            &trips,
+            date.unwrap_or(today),
+            &recover,
+            output,
+        ),
+        Command::Report {
+            date,
+            planned,
+            output,
+        } => {
+            let date = date.unwrap_or(today);
+            let planned = match planned {
+                Some(filename) => load_planned(&filename, &trips, date)?,
+                None => Vec::new(),
+            };
+            let report = engine.travel_report(&trips, &planned, date)?;
+            travel_report(&out, &report, output)
+        }
+        Command::Usage { end, regimes } => {
+            let regimes = if !regimes.is_empty() {
+                regimes
+            } else if let Some(traveller) = input
+                .traveller
+                .as_ref()
+                .filter(|traveller| !traveller.regimes.is_empty())
+            {
+                traveller
+                    .regimes
+                    .iter()
+                    .map(|name| name.parse())
+                    .collect::<Result<_>>()
+                    .with_context(|| format!("Traveller {}", traveller.name))?
+            } else {
+                Regime::builtin()
+            };
+            let num_unlabelled = regime::num_unlabelled(all_trips);
+            if regimes.len() > 1 && num_unlabelled > 0 {
+                let counted = if regimes.iter().any(|r| r.name == regime.name) {
+                    format!("counted under the {} regime only", regime.name)
+                } else {
+                    "not counted, select their regime with --regime".to_string()
+                };
+                out.warnings.push(format!(
+                    "{num_unlabelled} trip{} without a country {counted}",
+                    plural(num_unlabelled)
+                ));
+            }
+            let usages = regime::usages(
+                &regimes,
+                regime,
+                all_trips,
+                &input.permits,
+                end.unwrap_or(today),
             )?;
-            n += 1;
+            usage(&out, usages)
         }
-        Ok(())
+        Command::Srt {
+            year,
+            ties,
+            resident_before,
+            works_abroad,
+        } => {
+            let declaration = srt::Declaration {
+                ties,
+                resident_before,
+                works_abroad,
+            };
+            let year = year.unwrap_or_else(|| srt::tax_year_of(today));
+            warn_uncounted(&mut out.warnings, all_trips);
+            let test = srt::residence_test(all_trips, year, &declaration, today)?;
+            residence_test(&out, &test, today)
+        }
+        Command::TaxDays {
+            country,
+            year_start,
+            convention,
+            threshold,
+            planned,
+            from_year,
+            to_year,
+        } => {
+            let rule = TaxRule {
+                country,
+                year_start,
+                convention,
+                threshold,
+            };
+            let planned = match planned {
+                Some(filename) => load_planned(&filename, all_trips, today)?,
+                None => Vec::new(),
+            };
+            let first = all_trips.first().map_or(today, |trip| trip.entry);
+            let last = planned
+                .last()
+                .and_then(|trip| trip.exit)
+                .map_or(today, |exit| exit.max(today));
+            let years = from_year.unwrap_or_else(|| year_start.year_of(first))
+                ..=to_year.unwrap_or_else(|| year_start.year_of(last));
+            if rule.country.is_some() {
+                warn_uncounted(&mut out.warnings, all_trips);
+            }
+            let years = rule.count(all_trips, &planned, years, today)?;
+            tax_days(&out, rule, years)
+        }
+        Command::Spt { year, exempt } => {
+            let year = year.unwrap_or(today.year());
+            // Past years are counted to their end, with the regime usage on their last day.
+            let end = NaiveDate::from_ymd_opt(year, 12, 31)
+                .context("Bad year")?
+                .min(today);
+            warn_uncounted(&mut out.warnings, all_trips);
+            let test = spt::presence_test(all_trips, year, &exempt, end)?;
+            let usage = regime.usage(all_trips, &input.permits, end)?;
+            presence_test(&out, test, usage)
+        }
+        Command::Regimes => unreachable!("regimes are listed without trips"),
+        Command::Team { .. } => unreachable!("team report is made from the journal"),
     }
 }
 
-fn parse_date(s: &str) -> Result<NaiveDate> {
-    Ok(NaiveDate::parse_from_str(s, DATE_FMT)?)
+fn today() -> Result<NaiveDate> {
+    let now = Utc::now();
+    if let Some(date) = NaiveDate::from_ymd_opt(now.year(), now.month(), now.day()) {
+        Ok(date)
+    } else {
+        anyhow::bail!("Bad now");
+    }
 }
 
-fn parse_dates<R: BufRead>(mut reader: R) -> Result<Vec<NaiveDate>> {
-    let mut dates = Vec::new();
-    loop {
-        let mut buffer = String::new();
-        let bytes = reader.read_line(&mut buffer)?;
+fn open_file(filename: &str) -> Result<BufReader<File>> {
+    let file = OpenOptions::new()
+        .read(true)
+        .open(filename)
+        .with_context(|| format!("Cannot open {filename}"))?;
+    Ok(BufReader::new(file))
+}
 
-        if bytes == 0 {
-            // EOF reached
-            return Ok(dates);
-        } else {
-            let date = parse_date(buffer.trim())?;
-            dates.push(date);
+fn input_format(args: &TripArgs) -> InputFormat {
+    match (args.input_format, &args.file) {
+        (InputFormat::Auto, Some(filename)) => {
+            let filename = filename.to_lowercase();
+            if filename.ends_with(".csv") {
+                InputFormat::Csv
+            } else if filename.ends_with(".toml") {
+                InputFormat::Journal
+            } else if filename.ends_with(".ics") {
+                InputFormat::Ics
+            } else {
+                InputFormat::Lines
+            }
         }
+        (InputFormat::Auto, None) => InputFormat::Lines,
+        (input_format, _) => input_format,
+    }
+}
+
+fn open_input(args: &TripArgs) -> Result<Box<dyn BufRead>> {
+    Ok(if let Some(filename) = &args.file {
+        Box::new(open_file(filename)?)
+    } else {
+        Box::new(BufReader::new(io::stdin()))
+    })
+}
+
+fn load_journal(args: &TripArgs) -> Result<Journal> {
+    let mut s = String::new();
+    open_input(args)?.read_to_string(&mut s)?;
+    Journal::parse(&s)
+}
+
+/// Trips and the records that go with them, read from the input.
+struct Input {
+    trips: Vec<Trip>,
+    /// Permits given as options and, in a journal, the permits of the traveller.
+    permits: Vec<Permit>,
+    /// Traveller selected in a journal.
+    traveller: Option<Traveller>,
+    /// Built-in bilateral agreements, the agreements in a journal and those given as options.
+    agreements: Vec<Agreement>,
+}
+
+impl Input {
+    /// Nationality given as an option or written in the journal.
+    fn nationality(&self, args: &TripArgs) -> Option<String> {
+        args.nationality.clone().or_else(|| {
+            self.traveller
+                .as_ref()
+                .and_then(|traveller| traveller.nationality.clone())
+        })
     }
 }
 
-fn sort_and_dedup_dates(dates: &mut Vec<NaiveDate>) {
-    dates.sort();
-    let num_dates = dates.len();
-    dates.dedup();
-    let num_dups = num_dates - dates.len();
+fn load_input(args: &TripArgs, warnings: &mut Vec<String>) -> Result<Input> {
+    let mut permits = args.permit.clone();
+    let mut traveller = None;
+    let mut agreements = bilateral::agreements();
+    let mut trips = match input_format(args) {
+        InputFormat::Csv => parse_csv_trips(open_input(args)?, &args.columns)?,
+        InputFormat::Journal => {
+            let journal = load_journal(args)?;
+            let selected = journal.traveller(args.traveller.as_deref())?;
+            permits.extend(selected.permits()?);
+            let trips = selected.trips()?;
+            traveller = Some(selected.clone());
+            agreements.extend(journal.agreements);
+            trips
+        }
+        InputFormat::Ics => {
+            let filter = EventFilter {
+                category: args.ics_category.clone(),
+                keyword: args.ics_keyword.clone(),
+                location: args.ics_location.clone(),
+            };
+            let import = parse_ics_trips(open_input(args)?, &filter)?;
+            warnings.extend(import.warnings);
+            import.trips
+        }
+        _ => parse_trips(open_input(args)?)?,
+    };
+    agreements.extend(args.agreement.iter().cloned());
+
+    let num_dups = sort_trips(&mut trips)?;
     if num_dups > 0 {
-        println!(
-            "WARNING: {num_dups} duplicate date{} found and removed",
-            if num_dups > 1 { "s" } else { "" }
-        );
+        warnings.push(format!(
+            "{num_dups} duplicate trip{} found and removed",
+            plural(num_dups)
+        ));
     }
+    Ok(Input {
+        trips,
+        permits,
+        traveller,
+        agreements,
+    })
 }
 
-fn main() -> Result<()> {
-    let cli = Cli::parse();
+/// Reads planned trips, checking that they overlap neither each other nor the trip history. An
+/// open trip is taken to end on `until`, as it is when the planned trips are checked.
+fn load_planned(filename: &str, trips: &[Trip], until: NaiveDate) -> Result<Vec<Trip>> {
+    let mut planned = parse_trips(open_file(filename)?)?;
+    sort_trips(&mut planned)?;
+    let mut all_trips: Vec<_> = trips
+        .iter()
+        .map(|trip| {
+            let mut trip = trip.clone();
+            trip.exit = Some(trip.exit.unwrap_or(until.max(trip.entry)));
+            trip
+        })
+        .collect();
+    all_trips.extend(planned.iter().cloned());
+    sort_trips(&mut all_trips).context("Planned trips overlap the trip history")?;
+    Ok(planned)
+}
 
-    let control_period = Days::new(180);
-    let end_date = if let Some(date) = cli.end {
-        parse_date(&date)?
+fn plural(n: usize) -> &'static str {
+    if n == 1 {
+        ""
     } else {
-        let now = Utc::now();
-        if let Some(date) = NaiveDate::from_ymd_opt(now.year(), now.month(), now.day()) {
-            date
-        } else {
-            anyhow::bail!("Bad now");
+        "s"
+    }
+}
+
+/// Warns about the trips and legs that a count of the days in one country leaves out for having no
+/// country or a country that is not known.
+fn warn_uncounted(warnings: &mut Vec<String>, trips: &[Trip]) {
+    let num_unlabelled = regime::num_unlabelled(trips);
+    if num_unlabelled > 0 {
+        warnings.push(format!(
+            "{num_unlabelled} trip{} without a country not counted",
+            plural(num_unlabelled)
+        ));
+    }
+    let unknown: Vec<_> = trips
+        .iter()
+        .flat_map(Trip::split_legs)
+        .filter_map(|trip| trip.info.country)
+        .filter(|country| !membership::is_known_country(country))
+        .unique()
+        .collect();
+    if !unknown.is_empty() {
+        warnings.push(format!(
+            "Trips to unknown countries not counted: {}",
+            unknown.join(", ")
+        ));
+    }
+}
+
+#[derive(Serialize)]
+struct CheckResult {
+    rule: Engine,
+    #[serde(flatten)]
+    check: WindowCheck,
+    /// Trips counted in the control period with the counted dates.
+    trips: Vec<CountedTrip>,
+    /// Trip without an exit date and the last day to leave on it.
+    open_trip: Option<OpenTrip>,
+    /// Days in the control period covered by permits and not counted.
+    excluded: Vec<Exclusion>,
+    /// Whether the days over the allowance are covered by bilateral agreements.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    bilateral: Option<BilateralCheck>,
+}
+
+#[derive(Serialize)]
+struct CountedTrip {
+    #[serde(flatten)]
+    trip: Trip,
+    counted: DateInterval,
+}
+
+#[derive(Serialize)]
+struct OpenTrip {
+    entry: NaiveDate,
+    last_day: Option<NaiveDate>,
+}
+
+fn check(
+    out: &Output,
+    engine: &Engine,
+    trips: &[Trip],
+    excluded: Vec<Exclusion>,
+    bilateral: Option<BilateralCheck>,
+    end_date: NaiveDate,
+) -> Result<ExitCode> {
+    let stays = DateIntervalVec::from_trips(trips, end_date);
+    let check = engine.check(&stays, end_date)?;
+    let open_trip = if let Some(trip) = open_trip(trips) {
+        let history = DateIntervalVec::from_trips(&trips[..trips.len() - 1], end_date);
+        Some(OpenTrip {
+            entry: trip.entry,
+            last_day: engine.last_day(&history, trip.entry)?,
+        })
+    } else {
+        None
+    };
+    let trips = trips
+        .iter()
+        .filter_map(|trip| {
+            let counted = trip.clip(check.control_period)?;
+            Some(CountedTrip {
+                trip: trip.clone(),
+                counted,
+            })
+        })
+        .collect();
+    let result = CheckResult {
+        rule: *engine,
+        excluded: permit::clip_exclusions(excluded, check.control_period),
+        check,
+        trips,
+        open_trip,
+        bilateral,
+    };
+
+    out.emit(&result, |result| {
+        let check = &result.check;
+        println!("Visa control period is {}", check.control_period);
+        let intervals: Vec<_> = result
+            .trips
+            .iter()
+            .enumerate()
+            .map(|(n, t)| {
+                if t.trip.info.is_empty() {
+                    format!("{}) {}", n + 1, t.counted)
+                } else {
+                    format!("{}) {} ({})", n + 1, t.counted, t.trip.info)
+                }
+            })
+            .collect();
+        println!("Date intervals: {}", intervals.join(", "));
+        if !result.excluded.is_empty() {
+            let excluded: Vec<_> = result
+                .excluded
+                .iter()
+                .enumerate()
+                .map(|(n, exclusion)| format!("{}) {exclusion}", n + 1))
+                .collect();
+            println!("Days not counted: {}", excluded.join(", "));
+        }
+        println!("Days spent in the control period: {}", check.days_used);
+        println!("Days remaining: {}", check.days_remaining);
+        println!("Spent days {} the allowed number", check.verdict);
+        if let Some(bilateral) = &result.bilateral {
+            let countries: Vec<_> = bilateral
+                .countries
+                .iter()
+                .map(|c| {
+                    let country = c.country.as_deref().unwrap_or("an unknown country");
+                    match &c.agreement {
+                        Some(a) => format!("{} in {country} (agreement allows {})", c.days, a.days),
+                        None => format!("{} in {country} (no agreement)", c.days),
+                    }
+                })
+                .collect();
+            println!(
+                "The {} day{} over the allowance {} covered by bilateral agreements of {} \
+                 nationals: {}",
+                bilateral.days_over,
+                plural(bilateral.days_over),
+                if bilateral.covered { "are" } else { "are not" },
+                bilateral.nationality,
+                countries.join(", ")
+            );
+        }
+        match &result.open_trip {
+            Some(OpenTrip {
+                entry,
+                last_day: Some(last_day),
+            }) => println!("In the zone since {entry}, the last day to leave is {last_day}"),
+            Some(OpenTrip {
+                entry,
+                last_day: None,
+            }) => println!("Entering on {entry} was not allowed"),
+            None => {}
         }
+        Ok(())
+    })?;
+
+    Ok(match result.check.verdict {
+        Verdict::Within => ExitCode::SUCCESS,
+        Verdict::Exceeds => ExitCode::from(EXIT_NOT_ALLOWED),
+    })
+}
+
+#[derive(Serialize)]
+struct NextEntryResult {
+    from: NaiveDate,
+    earliest_entry: NaiveDate,
+}
+
+fn next_entry(out: &Output, engine: &Engine, trips: &[Trip], from: NaiveDate) -> Result<ExitCode> {
+    let stays = DateIntervalVec::from_trips(trips, from);
+    let result = NextEntryResult {
+        from,
+        earliest_entry: engine.earliest_entry(&stays, from)?,
     };
-    let start_date = end_date - control_period;
-    let control_period = DateInterval::new(start_date, end_date)?;
+    out.emit(&result, |result| {
+        println!("Earliest entry date: {}", result.earliest_entry);
+        Ok(())
+    })?;
+    Ok(ExitCode::SUCCESS)
+}
+
+#[derive(Serialize)]
+struct PlanResult {
+    entry: NaiveDate,
+    max_stay: usize,
+    /// Last day of the longest allowed stay, `None` if entering is not allowed.
+    last_day: Option<NaiveDate>,
+}
 
-    println!("Visa control period is {control_period}");
+fn plan(
+    out: &Output,
+    engine: &Engine,
+    trips: &[Trip],
+    today: NaiveDate,
+    entry: Option<NaiveDate>,
+) -> Result<ExitCode> {
+    let stays = DateIntervalVec::from_trips(trips, today);
+    let entry_date = if let Some(date) = entry {
+        date
+    } else {
+        engine.earliest_entry(&stays, today)?
+    };
+    let result = PlanResult {
+        entry: entry_date,
+        max_stay: engine.max_stay(&stays, entry_date)?,
+        last_day: engine.last_day(&stays, entry_date)?,
+    };
+    out.emit(&result, |result| {
+        if let Some(last_day) = result.last_day {
+            println!(
+                "Maximum stay when entering on {}: {} day{}, until {last_day}",
+                result.entry,
+                result.max_stay,
+                plural(result.max_stay)
+            );
+        } else {
+            println!("Entering on {} is not allowed", result.entry);
+        }
+        Ok(())
+    })?;
+    Ok(if result.last_day.is_some() {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::from(EXIT_NOT_ALLOWED)
+    })
+}
 
-    let mut dates = if let Some(filename) = cli.file {
-        let mut file = OpenOptions::new().read(true).open(filename)?;
+#[derive(Serialize)]
+struct PlannedTripsResult {
+    trips: Vec<PlannedTrip>,
+}
 
-        parse_dates(BufReader::new(&mut file))
+fn plan_trips(
+    out: &Output,
+    engine: &Engine,
+    trips: &[Trip],
+    planned: &[Trip],
+    until: NaiveDate,
+) -> Result<ExitCode> {
+    let history = DateIntervalVec::from_trips(trips, until);
+    let result = PlannedTripsResult {
+        trips: engine.check_planned(&history, planned)?,
+    };
+    out.emit(&result, |result| {
+        for (n, checked) in result.trips.iter().enumerate() {
+            let interval = checked
+                .trip
+                .interval(until)
+                .expect("planned trips are closed");
+            let Some(breach) = checked.breach else {
+                println!("{}) {interval}: allowed", n + 1);
+                continue;
+            };
+            println!(
+                "{}) {interval}: allowance exceeded from {} by up to {} day{}",
+                n + 1,
+                breach.first_day,
+                breach.days_over,
+                plural(breach.days_over)
+            );
+            if let Some(latest_exit) = breach.latest_exit {
+                println!("   The latest exit date for a legal trip is {latest_exit}");
+            } else {
+                println!("   Entering on {} is not allowed", checked.trip.entry);
+            }
+        }
+        Ok(())
+    })?;
+    Ok(if result.trips.iter().all(|t| t.breach.is_none()) {
+        ExitCode::SUCCESS
     } else {
-        parse_dates(BufReader::new(io::stdin()))
-    }?;
+        ExitCode::from(EXIT_NOT_ALLOWED)
+    })
+}
+
+#[derive(Serialize)]
+struct TimelineResult {
+    days: Vec<TimelineDay>,
+}
+
+fn timeline(
+    out: &Output,
+    engine: &Engine,
+    trips: &[Trip],
+    range: DateInterval,
+    csv: bool,
+) -> Result<ExitCode> {
+    let stays = DateIntervalVec::from_trips(trips, range.end());
+    let result = TimelineResult {
+        days: engine.timeline(&stays, range)?,
+    };
+    out.emit(&result, |result| {
+        if csv {
+            timeline::write_csv(io::stdout(), &result.days)
+        } else {
+            timeline::write_table(io::stdout(), &result.days)
+        }
+    })?;
+    Ok(ExitCode::SUCCESS)
+}
+
+#[derive(Serialize)]
+struct CalendarResult {
+    control_period: DateInterval,
+    days: Vec<CalendarDay>,
+}
+
+fn calendar(
+    out: &Output,
+    trips: &[Trip],
+    heatmap: &Heatmap,
+    control_period: DateInterval,
+) -> Result<ExitCode> {
+    let stays = DateIntervalVec::from_trips(trips, control_period.end());
+    let result = CalendarResult {
+        control_period,
+        days: heatmap.days(&stays, control_period),
+    };
+    out.emit(&result, |_| {
+        print!("{}", heatmap.render(&stays, control_period));
+        Ok(())
+    })?;
+    Ok(ExitCode::SUCCESS)
+}
+
+#[derive(Serialize)]
+struct OptimizeResult {
+    horizon: DateInterval,
+    trips: Vec<DateInterval>,
+    total_days: usize,
+}
+
+fn optimize(
+    out: &Output,
+    engine: &Engine,
+    trips: &[Trip],
+    constraints: &Constraints,
+) -> Result<ExitCode> {
+    let history = DateIntervalVec::from_trips(trips, constraints.horizon.start());
+    let schedule = engine.optimize(&history, constraints)?;
+    let result = OptimizeResult {
+        horizon: constraints.horizon,
+        total_days: schedule.iter().map(DateInterval::abs_num_days).sum(),
+        trips: schedule,
+    };
+    out.emit(&result, |result| {
+        for (n, trip) in result.trips.iter().enumerate() {
+            let num_days = trip.abs_num_days();
+            println!("{}) {trip}, {num_days} day{}", n + 1, plural(num_days));
+        }
+        println!(
+            "Total in the zone {}: {} day{}",
+            result.horizon,
+            result.total_days,
+            plural(result.total_days)
+        );
+        Ok(())
+    })?;
+    Ok(ExitCode::SUCCESS)
+}
+
+#[derive(Serialize)]
+struct HistoryResult {
+    trips: Vec<HistoryTrip>,
+    total_days: usize,
+}
+
+#[derive(Serialize)]
+struct HistoryTrip {
+    #[serde(flatten)]
+    trip: Trip,
+    /// Days spent on the trip so far, `None` if it has not started yet.
+    days: Option<usize>,
+}
 
-    sort_and_dedup_dates(&mut dates);
+fn history(out: &Output, trips: &[Trip], today: NaiveDate) -> Result<ExitCode> {
+    let trips: Vec<_> = trips
+        .iter()
+        .map(|trip| HistoryTrip {
+            trip: trip.clone(),
+            days: trip.interval(today).map(|di| di.abs_num_days()),
+        })
+        .collect();
+    let result = HistoryResult {
+        total_days: trips.iter().filter_map(|t| t.days).sum(),
+        trips,
+    };
+    out.emit(&result, |result| {
+        for (n, t) in result.trips.iter().enumerate() {
+            let Some(num_days) = t.days else {
+                println!("{}) {}, not started yet", n + 1, t.trip);
+                continue;
+            };
+            let exit = t.trip.exit.unwrap_or(today);
+            let legs: Vec<_> = t
+                .trip
+                .legs
+                .iter()
+                .map(|leg| format!("{} on {}", leg.country, leg.date))
+                .collect();
+            println!(
+                "{}) from {} to {exit}, {num_days} day{}{}{}{}",
+                n + 1,
+                t.trip.entry,
+                plural(num_days),
+                if t.trip.is_open() {
+                    ", still in the zone"
+                } else {
+                    ""
+                },
+                if t.trip.info.is_empty() {
+                    String::new()
+                } else {
+                    format!(" ({})", t.trip.info)
+                },
+                if legs.is_empty() {
+                    String::new()
+                } else {
+                    format!(", via {}", legs.join(", "))
+                }
+            );
+        }
+        println!(
+            "Total: {} day{}",
+            result.total_days,
+            plural(result.total_days)
+        );
+        Ok(())
+    })?;
+    Ok(ExitCode::SUCCESS)
+}
 
-    let date_intervals = DateIntervalVec::from_dates(&dates, control_period)?;
-    println!("Date intervals: {}", date_intervals);
+#[derive(Serialize)]
+struct ExportIcsResult {
+    milestones: Vec<Milestone>,
+}
 
-    let num_spent_days = date_intervals.num_spent_days();
-    println!("Days spent in the control period: {num_spent_days}");
-    println!(
-        "Spent days {} the allowed number",
-        if num_spent_days > cli.allowed {
-            "exceed"
+fn export_ics(
+    out: &Output,
+    engine: &Engine,
+    trips: &[Trip],
+    date: NaiveDate,
+    recover: &[usize],
+    output: Option<String>,
+) -> Result<ExitCode> {
+    let result = ExportIcsResult {
+        milestones: engine.milestones(trips, date, recover)?,
+    };
+    let stamp = Utc::now().naive_utc();
+    if let Some(filename) = &output {
+        let file = File::create(filename).with_context(|| format!("Cannot create {filename}"))?;
+        ics::write_milestones(BufWriter::new(file), &result.milestones, stamp)?;
+    }
+    out.emit(&result, |result| {
+        if output.is_some() {
+            for milestone in &result.milestones {
+                println!("{}: {}", milestone.date, milestone.summary);
+            }
+            Ok(())
+        } else {
+            ics::write_milestones(io::stdout(), &result.milestones, stamp)
+        }
+    })?;
+    Ok(ExitCode::SUCCESS)
+}
+
+fn travel_report(out: &Output, report: &TravelReport, output: Option<String>) -> Result<ExitCode> {
+    if let Some(filename) = &output {
+        let file = File::create(filename).with_context(|| format!("Cannot create {filename}"))?;
+        report::write_html(BufWriter::new(file), report)?;
+    }
+    out.emit(report, |report| {
+        if let Some(filename) = &output {
+            println!("Report on {} written to {filename}", report.date);
+            Ok(())
         } else {
-            "are within"
+            report::write_html(io::stdout(), report)
+        }
+    })?;
+    Ok(ExitCode::SUCCESS)
+}
+
+#[derive(Serialize)]
+struct RegimesResult {
+    regimes: Vec<Regime>,
+}
+
+fn regimes(out: &Output) -> Result<ExitCode> {
+    let result = RegimesResult {
+        regimes: Regime::builtin(),
+    };
+    out.emit(&result, |result| {
+        for regime in &result.regimes {
+            println!("{:<12} {}", regime.name, regime.description);
+        }
+        Ok(())
+    })?;
+    Ok(ExitCode::SUCCESS)
+}
+
+#[derive(Serialize)]
+struct UsageResult {
+    regimes: Vec<RegimeUsage>,
+}
+
+fn usage(out: &Output, usages: Vec<RegimeUsage>) -> Result<ExitCode> {
+    let result = UsageResult { regimes: usages };
+    out.emit(&result, |result| {
+        regime::write_usage_table(io::stdout(), &result.regimes)
+    })?;
+    let within = |u: &RegimeUsage| u.check.verdict == Verdict::Within;
+    Ok(if result.regimes.iter().all(within) {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::from(EXIT_NOT_ALLOWED)
+    })
+}
+
+fn residence_test(out: &Output, test: &ResidenceTest, today: NaiveDate) -> Result<ExitCode> {
+    out.emit(test, |test| {
+        println!("Tax year {} is {}", test.tax_year, test.period);
+        println!(
+            "Days in the UK at midnight{}: {}",
+            if today < test.period.end() {
+                " so far"
+            } else {
+                ""
+            },
+            test.uk_days
+        );
+        if let Some(needed) = test.ties_needed {
+            let ties: Vec<_> = test.ties.iter().map(Tie::to_string).collect();
+            println!(
+                "Ties to the UK: {} of {needed} needed{}",
+                ties.len(),
+                if ties.is_empty() {
+                    String::new()
+                } else {
+                    format!(" ({})", ties.join(", "))
+                }
+            );
         }
-    );
+        println!(
+            "{} in the UK by {}",
+            if test.resident {
+                "Resident"
+            } else {
+                "Not resident"
+            },
+            test.test
+        );
+        Ok(())
+    })?;
+    Ok(ExitCode::SUCCESS)
+}
 
-    Ok(())
+#[derive(Serialize)]
+struct SptResult {
+    #[serde(flatten)]
+    test: PresenceTest,
+    /// Days used under the regime on the last day counted.
+    usage: RegimeUsage,
+}
+
+fn presence_test(out: &Output, test: PresenceTest, usage: RegimeUsage) -> Result<ExitCode> {
+    let result = SptResult { test, usage };
+    out.emit(&result, |result| {
+        let test = &result.test;
+        println!("Substantial presence test for {}", test.year);
+        println!(
+            "Days in the US: {} in {}, {} in {} and {} in {}",
+            test.current_days,
+            test.year,
+            test.first_preceding_days,
+            test.year - 1,
+            test.second_preceding_days,
+            test.year - 2
+        );
+        if test.exempt_days > 0 {
+            println!("Exempt days not counted: {}", test.exempt_days);
+        }
+        println!(
+            "Weighted days: {:.1} of {}",
+            test.weighted_days,
+            spt::THRESHOLD_DAYS
+        );
+        match test.days_to_meet {
+            Some(days) => println!(
+                "The test is not met, {days} more day{} in the US in {} would meet it",
+                plural(days),
+                test.year
+            ),
+            None => println!("The test is met"),
+        }
+        let check = &result.usage.check;
+        println!(
+            "Days used under the {} regime on {}: {}, remaining: {}",
+            result.usage.regime,
+            check.control_period.end(),
+            check.days_used,
+            check.days_remaining
+        );
+        Ok(())
+    })?;
+    Ok(ExitCode::SUCCESS)
+}
+
+#[derive(Serialize)]
+struct TaxDaysResult {
+    rule: TaxRule,
+    years: Vec<TaxYearDays>,
+}
+
+fn tax_days(out: &Output, rule: TaxRule, years: Vec<TaxYearDays>) -> Result<ExitCode> {
+    let result = TaxDaysResult { rule, years };
+    out.emit(&result, |result| {
+        let rule = &result.rule;
+        println!(
+            "Days in {} counting {}, with {} days making a tax resident",
+            rule.country.as_deref().unwrap_or("the country"),
+            rule.convention,
+            rule.threshold
+        );
+        tax::write_table(io::stdout(), &result.years)
+    })?;
+    Ok(ExitCode::SUCCESS)
+}
+
+#[derive(Serialize)]
+struct TeamResult {
+    travellers: Vec<TravellerStatus>,
+}
+
+fn team_report(out: &Output, travellers: Vec<TravellerStatus>) -> Result<ExitCode> {
+    let result = TeamResult { travellers };
+    out.emit(&result, |result| {
+        team::write_table(io::stdout(), &result.travellers)
+    })?;
+    Ok(if result.travellers.iter().all(|s| s.compliant) {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::from(EXIT_NOT_ALLOWED)
+    })
 }
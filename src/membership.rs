@@ -0,0 +1,164 @@
+//! Countries of a visa zone and the dates they joined it.
+
+use crate::{parse_date, Border};
+use chrono::NaiveDate;
+use serde::Serialize;
+
+/// Membership of a country in a visa zone. Checks at internal borders may have been lifted on
+/// different dates for land, sea and air borders.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Membership {
+    /// ISO 3166-1 alpha-2 code of the country.
+    pub code: String,
+    /// English name of the country.
+    pub name: String,
+    /// First day of membership for entries over land borders.
+    #[serde(skip_serializing_if = "is_always")]
+    pub land: NaiveDate,
+    /// First day of membership for entries over sea borders.
+    #[serde(skip_serializing_if = "is_always")]
+    pub sea: NaiveDate,
+    /// First day of membership for entries by air.
+    #[serde(skip_serializing_if = "is_always")]
+    pub air: NaiveDate,
+}
+
+fn is_always(date: &NaiveDate) -> bool {
+    *date == NaiveDate::MIN
+}
+
+impl Membership {
+    /// Member without a date of joining.
+    pub fn always(code: &str, name: &str) -> Self {
+        Self {
+            code: code.to_string(),
+            name: name.to_string(),
+            land: NaiveDate::MIN,
+            sea: NaiveDate::MIN,
+            air: NaiveDate::MIN,
+        }
+    }
+
+    /// First day on which days spent in the country count towards the zone, for an entry crossing
+    /// `border`. If the way of entry is unknown, the earliest date is used so that no day in the
+    /// zone is missed.
+    pub fn since(&self, border: Option<Border>) -> NaiveDate {
+        match border {
+            Some(Border::Land) => self.land,
+            Some(Border::Sea) => self.sea,
+            Some(Border::Air) => self.air,
+            None => self.land.min(self.sea).min(self.air),
+        }
+    }
+
+    /// Checks whether `country` is the code or the name of the member, ignoring case.
+    pub fn matches(&self, country: &str) -> bool {
+        let country = country.trim();
+        self.code.eq_ignore_ascii_case(country) || self.name.eq_ignore_ascii_case(country)
+    }
+}
+
+/// Schengen area members with the dates of lifting checks at internal land, sea and air borders.
+/// Liechtenstein has no airport or sea port, so all its dates are the same.
+const SCHENGEN: &[(&str, &str, &str, &str, &str)] = &[
+    ("AT", "Austria", "1997-12-01", "1997-12-01", "1997-12-01"),
+    ("BE", "Belgium", "1995-03-26", "1995-03-26", "1995-03-26"),
+    ("BG", "Bulgaria", "2025-01-01", "2024-03-31", "2024-03-31"),
+    (
+        "CH",
+        "Switzerland",
+        "2008-12-12",
+        "2008-12-12",
+        "2009-03-29",
+    ),
+    ("CZ", "Czechia", "2007-12-21", "2007-12-21", "2008-03-30"),
+    ("DE", "Germany", "1995-03-26", "1995-03-26", "1995-03-26"),
+    ("DK", "Denmark", "2001-03-25", "2001-03-25", "2001-03-25"),
+    ("EE", "Estonia", "2007-12-21", "2007-12-21", "2008-03-30"),
+    ("ES", "Spain", "1995-03-26", "1995-03-26", "1995-03-26"),
+    ("FI", "Finland", "2001-03-25", "2001-03-25", "2001-03-25"),
+    ("FR", "France", "1995-03-26", "1995-03-26", "1995-03-26"),
+    ("GR", "Greece", "2000-03-26", "2000-03-26", "2000-03-26"),
+    // Croatia applied the rules on all its borders from 2023-01-01, only the checks at internal air
+    // borders were lifted later, on 2023-03-26.
+    ("HR", "Croatia", "2023-01-01", "2023-01-01", "2023-01-01"),
+    ("HU", "Hungary", "2007-12-21", "2007-12-21", "2008-03-30"),
+    ("IS", "Iceland", "2001-03-25", "2001-03-25", "2001-03-25"),
+    ("IT", "Italy", "1997-10-26", "1997-10-26", "1997-10-26"),
+    (
+        "LI",
+        "Liechtenstein",
+        "2011-12-19",
+        "2011-12-19",
+        "2011-12-19",
+    ),
+    ("LT", "Lithuania", "2007-12-21", "2007-12-21", "2008-03-30"),
+    ("LU", "Luxembourg", "1995-03-26", "1995-03-26", "1995-03-26"),
+    ("LV", "Latvia", "2007-12-21", "2007-12-21", "2008-03-30"),
+    ("MT", "Malta", "2007-12-21", "2007-12-21", "2008-03-30"),
+    (
+        "NL",
+        "Netherlands",
+        "1995-03-26",
+        "1995-03-26",
+        "1995-03-26",
+    ),
+    ("NO", "Norway", "2001-03-25", "2001-03-25", "2001-03-25"),
+    ("PL", "Poland", "2007-12-21", "2007-12-21", "2008-03-30"),
+    ("PT", "Portugal", "1995-03-26", "1995-03-26", "1995-03-26"),
+    ("RO", "Romania", "2025-01-01", "2024-03-31", "2024-03-31"),
+    ("SE", "Sweden", "2001-03-25", "2001-03-25", "2001-03-25"),
+    ("SI", "Slovenia", "2007-12-21", "2007-12-21", "2008-03-30"),
+    ("SK", "Slovakia", "2007-12-21", "2007-12-21", "2008-03-30"),
+];
+
+/// Other names of countries outside the Schengen area, by country code.
+const ALIASES: &[(&str, &[&str])] = &[
+    (
+        "GB",
+        &[
+            "UK",
+            "United Kingdom",
+            "Great Britain",
+            "England",
+            "Scotland",
+            "Wales",
+            "Northern Ireland",
+        ],
+    ),
+    ("TR", &["Turkey", "Türkiye"]),
+    ("US", &["USA", "United States", "United States of America"]),
+];
+
+/// Members of the Schengen area.
+pub fn schengen_members() -> Vec<Membership> {
+    SCHENGEN
+        .iter()
+        .map(|&(code, name, land, sea, air)| Membership {
+            code: code.to_string(),
+            name: name.to_string(),
+            land: parse_date(land).expect("valid date in the membership table"),
+            sea: parse_date(sea).expect("valid date in the membership table"),
+            air: parse_date(air).expect("valid date in the membership table"),
+        })
+        .collect()
+}
+
+/// Code of a Schengen member or of a country with known names, given by its code or name, or the
+/// country in upper case otherwise.
+pub fn country_code(country: &str) -> String {
+    let country = country.trim();
+    if let Some(member) = schengen_members().into_iter().find(|m| m.matches(country)) {
+        return member.code;
+    }
+    ALIASES
+        .iter()
+        .find(|(_, names)| names.iter().any(|name| name.eq_ignore_ascii_case(country)))
+        .map_or_else(|| country.to_uppercase(), |(code, _)| code.to_string())
+}
+
+/// Checks whether `country` is a two-letter country code, or a name with a known code.
+pub fn is_known_country(country: &str) -> bool {
+    let code = country_code(country);
+    code.len() == 2 && code.chars().all(|c| c.is_ascii_alphabetic())
+}
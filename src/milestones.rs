@@ -0,0 +1,90 @@
+//! Deadlines and allowance milestones of a traveller.
+
+use crate::{open_trip, DateIntervalVec, Engine, Trip};
+use anyhow::Result;
+use chrono::{Days, NaiveDate};
+use serde::Serialize;
+
+/// Notable date for planning trips.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Milestone {
+    pub date: NaiveDate,
+    pub kind: MilestoneKind,
+    pub summary: String,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case", tag = "type")]
+pub enum MilestoneKind {
+    /// Last day to leave the zone on the current trip.
+    LeaveBy,
+    /// Earliest date on which entering the zone is allowed.
+    EarliestEntry,
+    /// First date with at least `days` days of allowance available.
+    Recovery { days: usize },
+}
+
+impl Engine {
+    /// Finds the first date on or after `from` with at least `days` days of allowance remaining.
+    /// Returns `None` if no stay can last `days` days.
+    pub fn recovery_date(
+        &self,
+        stays: &DateIntervalVec,
+        from: NaiveDate,
+        days: usize,
+    ) -> Result<Option<NaiveDate>> {
+        if days > self.limit() {
+            return Ok(None);
+        }
+        self.find_date(from, |date| {
+            Ok(self.check(stays, date)?.days_remaining >= days)
+        })
+    }
+
+    /// Calculates the milestones from `date` on: the last day to leave on an open trip, the
+    /// earliest entry date and the dates on which each of `recover` days of allowance become
+    /// available. An open trip is counted until the last day to leave it, and no trips are assumed
+    /// after that.
+    pub fn milestones(
+        &self,
+        trips: &[Trip],
+        date: NaiveDate,
+        recover: &[usize],
+    ) -> Result<Vec<Milestone>> {
+        let mut milestones = Vec::new();
+        let mut until = date;
+        let mut from = date;
+        if let Some(trip) = open_trip(trips) {
+            let history = DateIntervalVec::from_trips(&trips[..trips.len() - 1], date);
+            if let Some(last_day) = self.last_day(&history, trip.entry)? {
+                milestones.push(Milestone {
+                    date: last_day,
+                    kind: MilestoneKind::LeaveBy,
+                    summary: "Last day to leave the zone".to_string(),
+                });
+                if last_day >= date {
+                    until = last_day;
+                    from = last_day + Days::new(1);
+                }
+            }
+        }
+
+        let stays = DateIntervalVec::from_trips(trips, until);
+        milestones.push(Milestone {
+            date: self.earliest_entry(&stays, from)?,
+            kind: MilestoneKind::EarliestEntry,
+            summary: "Earliest entry into the zone".to_string(),
+        });
+        for &days in recover {
+            if let Some(recovery_date) = self.recovery_date(&stays, from, days)? {
+                milestones.push(Milestone {
+                    date: recovery_date,
+                    kind: MilestoneKind::Recovery { days },
+                    summary: format!("{days} days of visa allowance available"),
+                });
+            }
+        }
+        milestones.sort_by_key(|m| m.date);
+        Ok(milestones)
+    }
+}
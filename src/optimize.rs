@@ -0,0 +1,174 @@
+//! Scheduling of trips that maximise the days spent in the zone.
+
+use crate::{DateInterval, DateIntervalVec, Engine};
+use anyhow::Result;
+use chrono::{Days, NaiveDate};
+use std::collections::HashMap;
+
+/// Constraints on the trip schedule.
+#[derive(Debug, Clone)]
+pub struct Constraints {
+    /// Dates the schedule covers.
+    pub horizon: DateInterval,
+    /// Dates that have to be spent outside the zone.
+    pub outside: Vec<DateInterval>,
+    /// Minimum length of a trip in days.
+    pub min_trip_days: usize,
+}
+
+impl Constraints {
+    fn is_outside(&self, date: NaiveDate) -> bool {
+        self.outside.iter().any(|di| di.contains(date))
+    }
+
+    /// Number of consecutive days from `date` that are in the horizon and not required to be spent
+    /// outside the zone.
+    fn free_days_from(&self, date: NaiveDate) -> usize {
+        let mut num_days = 0;
+        let mut d = date;
+        while d <= self.horizon.end() && !self.is_outside(d) {
+            num_days += 1;
+            d = d + Days::new(1);
+        }
+        num_days
+    }
+}
+
+impl Engine {
+    /// Makes a schedule of trips within the horizon that maximises the total number of days in the
+    /// zone, in addition to the `history` of stays.
+    ///
+    /// Trips are searched by their entry day and length, longest first. Entering as early as
+    /// allowed and staying as long as allowed gives the most days when trips can be of any length,
+    /// so that schedule bounds what the rest of the horizon can add, and branches that cannot beat
+    /// the best schedule found are cut.
+    pub fn optimize(
+        &self,
+        history: &DateIntervalVec,
+        constraints: &Constraints,
+    ) -> Result<Vec<DateInterval>> {
+        let min_trip_days = constraints.min_trip_days.max(1);
+        let start = constraints.horizon.start();
+        let greedy = self.greedy_schedule(history, constraints, start, min_trip_days)?;
+        if min_trip_days == 1 {
+            return Ok(greedy);
+        }
+        let mut search = Search {
+            engine: self,
+            constraints,
+            min_trip_days,
+            best_total: total_days(&greedy),
+            best: greedy,
+            visited: HashMap::new(),
+        };
+        search.run(start, history, &mut Vec::new(), 0)?;
+        Ok(search.best)
+    }
+
+    /// Schedule entering on the first day a trip of at least `min_trip_days` is allowed and staying
+    /// as long as allowed, from `from` to the end of the horizon.
+    fn greedy_schedule(
+        &self,
+        history: &DateIntervalVec,
+        constraints: &Constraints,
+        from: NaiveDate,
+        min_trip_days: usize,
+    ) -> Result<Vec<DateInterval>> {
+        let mut stays = history.clone();
+        let mut schedule = Vec::new();
+        let mut date = from;
+        while date <= constraints.horizon.end() {
+            let num_days = self
+                .max_stay(&stays, date)?
+                .min(constraints.free_days_from(date));
+            if num_days < min_trip_days {
+                date = date + Days::new(1);
+                continue;
+            }
+            let exit = date + Days::new(num_days as u64 - 1);
+            let trip = DateInterval::new(date, exit)?;
+            schedule.push(trip);
+            stays = stays.with(trip);
+            date = exit + Days::new(1);
+        }
+        Ok(schedule)
+    }
+}
+
+fn total_days(schedule: &[DateInterval]) -> usize {
+    schedule.iter().map(DateInterval::abs_num_days).sum()
+}
+
+/// Branch-and-bound search for the schedule with the most days.
+struct Search<'a> {
+    engine: &'a Engine,
+    constraints: &'a Constraints,
+    min_trip_days: usize,
+    best: Vec<DateInterval>,
+    best_total: usize,
+    /// Most days reached on a day with the same stays in the control periods ahead.
+    visited: HashMap<(NaiveDate, Vec<(NaiveDate, NaiveDate)>), usize>,
+}
+
+impl Search<'_> {
+    /// Extends `schedule`, which adds `total` days to the history, with trips entering on `date` or
+    /// later.
+    fn run(
+        &mut self,
+        date: NaiveDate,
+        stays: &DateIntervalVec,
+        schedule: &mut Vec<DateInterval>,
+        total: usize,
+    ) -> Result<()> {
+        if date > self.constraints.horizon.end() {
+            if total > self.best_total {
+                self.best_total = total;
+                self.best = schedule.clone();
+            }
+            return Ok(());
+        }
+
+        // The stays before the control periods of the remaining days no longer matter.
+        let ahead = DateInterval::new(self.engine.control_period(date)?.start(), NaiveDate::MAX)?;
+        let key = (
+            date,
+            stays
+                .clip(ahead)
+                .as_slice()
+                .iter()
+                .map(|di| (di.start(), di.end()))
+                .collect(),
+        );
+        if self
+            .visited
+            .get(&key)
+            .is_some_and(|&reached| reached >= total)
+        {
+            return Ok(());
+        }
+        self.visited.insert(key, total);
+
+        let bound = self
+            .engine
+            .greedy_schedule(stays, self.constraints, date, 1)?;
+        if total + total_days(&bound) <= self.best_total {
+            return Ok(());
+        }
+
+        if !self.constraints.is_outside(date) {
+            let max_days = self
+                .engine
+                .max_stay(stays, date)?
+                .min(self.constraints.free_days_from(date));
+            for num_days in (self.min_trip_days..=max_days).rev() {
+                let trip = DateInterval::new(date, date + Days::new(num_days as u64 - 1))?;
+                schedule.push(trip);
+                // The day after the trip is spent outside the zone, or the trip would be longer.
+                let next = trip.end() + Days::new(2);
+                self.run(next, &stays.with(trip), schedule, total + num_days)?;
+                schedule.pop();
+            }
+        }
+        self.run(date + Days::new(1), stays, schedule, total)
+    }
+}
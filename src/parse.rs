@@ -0,0 +1,69 @@
+//! Parsing of trip lists.
+
+use crate::{DateInterval, Trip, DATE_FMT};
+use anyhow::{Context, Result};
+use chrono::NaiveDate;
+use std::io::BufRead;
+
+pub fn parse_date(s: &str) -> Result<NaiveDate> {
+    NaiveDate::parse_from_str(s, DATE_FMT).with_context(|| format!("Invalid date '{s}'"))
+}
+
+/// Parses a date interval written as `FROM..TO`, or a single date for a one-day interval.
+pub fn parse_interval(s: &str) -> Result<DateInterval> {
+    if let Some((a, b)) = s.split_once("..") {
+        DateInterval::new(parse_date(a)?, parse_date(b)?)
+    } else {
+        let d = parse_date(s)?;
+        DateInterval::new(d, d)
+    }
+}
+
+/// Reads trips with dates in `YYYY-MM-DD` format.
+///
+/// Each line contains either the entry and exit dates of a trip separated by whitespace or a
+/// comma, or a single date. Consecutive single-date lines are paired up as the entry and exit
+/// dates of a trip in the order they appear. A single date on the last line is the entry date of
+/// an open trip. Empty lines and lines starting with `#` are skipped.
+pub fn parse_trips<R: BufRead>(reader: R) -> Result<Vec<Trip>> {
+    let mut trips = Vec::new();
+    // Entry date waiting for its exit date on the next line.
+    let mut pending: Option<(NaiveDate, usize)> = None;
+    for (i, line) in reader.lines().enumerate() {
+        let line_num = i + 1;
+        let line = line?;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let fields: Vec<_> = line
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|s| !s.is_empty())
+            .collect();
+        let dates = fields
+            .iter()
+            .map(|s| parse_date(s))
+            .collect::<Result<Vec<_>>>()
+            .with_context(|| format!("Line {line_num}"))?;
+        match (dates.as_slice(), pending) {
+            (&[date], None) => pending = Some((date, line_num)),
+            (&[exit], Some((entry, entry_line))) => {
+                let trip = Trip::new(entry, exit).with_context(|| format!("Line {line_num}"))?;
+                trips.push(trip.at_line(entry_line));
+                pending = None;
+            }
+            (&[entry, exit], None) => {
+                let trip = Trip::new(entry, exit).with_context(|| format!("Line {line_num}"))?;
+                trips.push(trip.at_line(line_num));
+            }
+            (&[_, _], Some((_, entry_line))) => {
+                anyhow::bail!("Line {entry_line}: entry date has no exit date");
+            }
+            _ => anyhow::bail!("Line {line_num}: expected one or two dates"),
+        }
+    }
+    if let Some((entry, entry_line)) = pending {
+        trips.push(Trip::open(entry).at_line(entry_line));
+    }
+    Ok(trips)
+}
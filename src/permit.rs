@@ -0,0 +1,145 @@
+//! Long-stay visas and residence permits, whose days are not counted as short stays.
+
+use crate::{parse_interval, DateInterval, Trip};
+use anyhow::{Context, Result};
+use chrono::{Days, NaiveDate};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// Kind of document allowing a stay beyond the short-stay rules.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum PermitKind {
+    /// National long-stay (type D) visa.
+    #[serde(alias = "d-visa")]
+    LongStayVisa,
+    ResidencePermit,
+}
+
+impl fmt::Display for PermitKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::LongStayVisa => write!(f, "long-stay visa"),
+            Self::ResidencePermit => write!(f, "residence permit"),
+        }
+    }
+}
+
+impl FromStr for PermitKind {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "long-stay-visa" | "d-visa" => Ok(Self::LongStayVisa),
+            "residence-permit" => Ok(Self::ResidencePermit),
+            _ => anyhow::bail!(
+                "Unknown permit '{s}', expected long-stay-visa, d-visa or residence-permit"
+            ),
+        }
+    }
+}
+
+/// Period covered by a long-stay visa or a residence permit. Days spent in the zone in this period
+/// are not counted.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Permit {
+    pub kind: PermitKind,
+    /// Country that issued the permit. A permit without a country applies to any regime.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub country: Option<String>,
+    pub period: DateInterval,
+}
+
+impl fmt::Display for Permit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.kind)?;
+        if let Some(country) = &self.country {
+            write!(f, " of {country}")?;
+        }
+        write!(f, " {}", self.period)
+    }
+}
+
+impl FromStr for Permit {
+    type Err = anyhow::Error;
+
+    /// Parses a permit written as `KIND:FROM..TO`, for example `d-visa:2024-01-01..2024-12-31`.
+    fn from_str(s: &str) -> Result<Self> {
+        let (kind, period) = s
+            .split_once(':')
+            .with_context(|| format!("Expected KIND:FROM..TO, got '{s}'"))?;
+        Ok(Self {
+            kind: kind.parse()?,
+            country: None,
+            period: parse_interval(period)?,
+        })
+    }
+}
+
+/// Days of a trip that were not counted because they were covered by a permit.
+#[derive(Debug, Clone, Serialize)]
+pub struct Exclusion {
+    pub days: DateInterval,
+    pub permit: Permit,
+}
+
+impl fmt::Display for Exclusion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}, covered by the {}", self.days, self.permit)
+    }
+}
+
+/// Removes the days covered by `permits` from the trips. A trip partly covered by a permit is split
+/// into the parts before and after the permit. Returns the remaining trips in order and the
+/// excluded days.
+pub fn exclude_permits(trips: &[Trip], permits: &[Permit]) -> (Vec<Trip>, Vec<Exclusion>) {
+    let mut exclusions = Vec::new();
+    let mut remaining = trips.to_vec();
+    for permit in permits {
+        let (start, end) = (permit.period.start(), permit.period.end());
+        let mut parts = Vec::with_capacity(remaining.len());
+        for trip in remaining {
+            let exit = trip.exit.unwrap_or(NaiveDate::MAX);
+            if exit < start || end < trip.entry {
+                parts.push(trip);
+                continue;
+            }
+            let covered = DateInterval::new(trip.entry.max(start), exit.min(end))
+                .expect("the trip overlaps the permit");
+            exclusions.push(Exclusion {
+                days: covered,
+                permit: permit.clone(),
+            });
+            if trip.entry < start {
+                let mut before = trip.clone();
+                before.exit = Some(start - Days::new(1));
+                parts.push(before);
+            }
+            if end < exit {
+                let mut after = trip;
+                after.entry = end + Days::new(1);
+                parts.push(after);
+            }
+        }
+        remaining = parts;
+    }
+    remaining.sort_by_key(|trip| trip.entry);
+    exclusions.sort_by_key(|exclusion| exclusion.days.start());
+    (remaining, exclusions)
+}
+
+/// Keeps the excluded days in `control_period`, clipped to it.
+pub fn clip_exclusions(exclusions: Vec<Exclusion>, control_period: DateInterval) -> Vec<Exclusion> {
+    exclusions
+        .into_iter()
+        .filter_map(|mut exclusion| {
+            if !exclusion.days.overlaps(control_period) {
+                return None;
+            }
+            exclusion.days.start_no_earlier(control_period.start());
+            exclusion.days.end_no_later(control_period.end());
+            Some(exclusion)
+        })
+        .collect()
+}
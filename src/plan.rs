@@ -0,0 +1,89 @@
+//! Validation of planned trips.
+
+use crate::{open_trip, DateIntervalVec, Engine, Trip, Verdict};
+use anyhow::Result;
+use chrono::{Days, NaiveDate};
+use serde::Serialize;
+
+/// Result of checking a planned trip day by day.
+#[derive(Debug, Clone, Serialize)]
+pub struct PlannedTrip {
+    pub trip: Trip,
+    /// Where the trip breaks the rule, if it does.
+    pub breach: Option<Breach>,
+}
+
+/// How a planned trip breaks the rule.
+#[derive(Debug, Copy, Clone, Serialize)]
+pub struct Breach {
+    /// First day on which the allowance is exceeded.
+    pub first_day: NaiveDate,
+    /// Largest number of days over the allowance or the maximum stay on any day of the trip.
+    pub days_over: usize,
+    /// Latest exit date that makes the trip legal. `None` if entering on the planned entry date is
+    /// not allowed.
+    pub latest_exit: Option<NaiveDate>,
+}
+
+impl Engine {
+    /// Checks every day of every planned trip against the control period ending on that day.
+    /// Each trip is counted together with `history` and all planned trips before it.
+    pub fn check_planned(
+        &self,
+        history: &DateIntervalVec,
+        planned: &[Trip],
+    ) -> Result<Vec<PlannedTrip>> {
+        let mut stays = history.clone();
+        let mut checked = Vec::with_capacity(planned.len());
+        for trip in planned {
+            let Some(exit) = trip.exit else {
+                anyhow::bail!("The planned {trip} has no exit date");
+            };
+            let with_trip = stays.with(trip.interval(exit).expect("trip is closed"));
+            let mut breach: Option<Breach> = None;
+            let mut date = trip.entry;
+            while date <= exit {
+                let check = self.check(&with_trip, date)?;
+                if check.verdict == Verdict::Exceeds {
+                    let days_over = check.days_over;
+                    if let Some(breach) = &mut breach {
+                        breach.days_over = breach.days_over.max(days_over);
+                    } else {
+                        breach = Some(Breach {
+                            first_day: date,
+                            days_over,
+                            latest_exit: self.last_day(&stays, trip.entry)?,
+                        });
+                    }
+                }
+                date = date + Days::new(1);
+            }
+            checked.push(PlannedTrip {
+                trip: trip.clone(),
+                breach,
+            });
+            stays = with_trip;
+        }
+        Ok(checked)
+    }
+
+    /// Last day on which the open trip is counted when checking the `planned` trips after it: the
+    /// last day to leave the zone, or the day before the first planned trip if that is earlier, but
+    /// not before `today`. Returns `today` if no trip is open.
+    pub fn open_trip_until(
+        &self,
+        trips: &[Trip],
+        planned: &[Trip],
+        today: NaiveDate,
+    ) -> Result<NaiveDate> {
+        let Some(trip) = open_trip(trips) else {
+            return Ok(today);
+        };
+        let history = DateIntervalVec::from_trips(&trips[..trips.len() - 1], today);
+        let mut until = self.last_day(&history, trip.entry)?.unwrap_or(today);
+        if let Some(first) = planned.iter().map(|trip| trip.entry).min() {
+            until = until.min(first - Days::new(1));
+        }
+        Ok(until.max(today))
+    }
+}
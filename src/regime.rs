@@ -0,0 +1,288 @@
+//! Visa regimes: named rolling-window rules with the dates they apply on.
+
+use crate::membership::{self, Membership};
+use crate::permit::{clip_exclusions, exclude_permits, Exclusion, Permit};
+use crate::{
+    Convention, DateIntervalVec, Engine, Trip, Verdict, WindowCheck, ALLOWED_DAYS,
+    CONTROL_PERIOD_DAYS,
+};
+use anyhow::Result;
+use chrono::{Days, NaiveDate};
+use serde::Serialize;
+use std::io::Write;
+use std::str::FromStr;
+
+/// Rules of a visa regime.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Regime {
+    pub name: String,
+    pub description: String,
+    /// Number of days in the control period.
+    pub period: usize,
+    /// Maximum number of days allowed in the control period, `None` if only the length of a single
+    /// stay is limited.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub allowed: Option<usize>,
+    /// Maximum length of a single stay in days, if limited.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_stay: Option<usize>,
+    /// Days of a stay counted as days spent in the zone.
+    pub counting: Convention,
+    /// First day on which the rules apply. Days spent in the zone before it are not counted.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub effective_from: Option<NaiveDate>,
+    /// Last day on which the rules apply. Days spent in the zone after it are not counted.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub effective_until: Option<NaiveDate>,
+    /// Countries of the zone. If empty, trips to any country are counted.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub members: Vec<Membership>,
+}
+
+/// Days used under a regime in the control period ending on a date.
+#[derive(Debug, Clone, Serialize)]
+pub struct RegimeUsage {
+    pub regime: String,
+    #[serde(flatten)]
+    pub check: WindowCheck,
+    /// Days in the control period covered by permits and not counted.
+    pub excluded: Vec<Exclusion>,
+}
+
+impl Regime {
+    /// Regimes known to the calculator. The first one is the default.
+    pub fn builtin() -> Vec<Regime> {
+        vec![
+            Regime {
+                name: "schengen".to_string(),
+                description: "Schengen area short stays, 90 days in any 180".to_string(),
+                period: CONTROL_PERIOD_DAYS,
+                allowed: Some(ALLOWED_DAYS),
+                max_stay: None,
+                counting: Convention::AnyPart,
+                // The rolling 180-day period replaced the period starting on the first entry.
+                effective_from: NaiveDate::from_ymd_opt(2013, 10, 18),
+                effective_until: None,
+                members: membership::schengen_members(),
+            },
+            Regime {
+                name: "turkey".to_string(),
+                description: "Turkey visa exemption, 90 days in any 180".to_string(),
+                period: 180,
+                allowed: Some(90),
+                max_stay: None,
+                counting: Convention::AnyPart,
+                effective_from: None,
+                effective_until: None,
+                members: vec![Membership::always("TR", "Turkey")],
+            },
+            Regime {
+                name: "uk-visitor".to_string(),
+                description: "UK standard visitor, up to 6 months per visit".to_string(),
+                // Only the length of a visit is limited. Six months are counted as 180 days.
+                period: 180,
+                allowed: None,
+                max_stay: Some(180),
+                counting: Convention::AnyPart,
+                effective_from: None,
+                effective_until: None,
+                members: vec![Membership::always("GB", "United Kingdom")],
+            },
+        ]
+    }
+
+    /// Engine that checks the rules of the regime.
+    pub fn engine(&self) -> Engine {
+        match self.allowed {
+            Some(allowed) => Engine::new(self.period, allowed).with_max_stay(self.max_stay),
+            None => Engine::single_stay(self.max_stay.unwrap_or(self.period)),
+        }
+    }
+
+    /// Finds the member by its country code or name.
+    pub fn member(&self, country: &str) -> Option<&Membership> {
+        self.members.iter().find(|m| m.matches(country))
+    }
+
+    /// Checks whether the regime applies to trips to `country`.
+    pub fn covers(&self, country: &str) -> bool {
+        self.members.is_empty() || self.member(country).is_some()
+    }
+
+    /// Trips counted under the regime: the days of the applicable trips counted by the regime,
+    /// without the days covered by the permits issued by a member country or by no particular
+    /// country. Returns the counted trips and the excluded days.
+    pub fn counted_trips(&self, trips: &[Trip], permits: &[Permit]) -> (Vec<Trip>, Vec<Exclusion>) {
+        let permits: Vec<_> = permits
+            .iter()
+            .filter(|permit| permit.country.as_deref().is_none_or(|c| self.covers(c)))
+            .cloned()
+            .collect();
+        let trips: Vec<_> = self
+            .applicable_trips(trips)
+            .into_iter()
+            .filter_map(|trip| self.counted_days(trip))
+            .collect();
+        exclude_permits(&trips, &permits)
+    }
+
+    /// Cuts the trip to the days counted under the counting rules of the regime. Returns `None` if
+    /// no day is counted. Open trips are not cut.
+    fn counted_days(&self, mut trip: Trip) -> Option<Trip> {
+        let Some(exit) = trip.exit else {
+            return Some(trip);
+        };
+        match self.counting {
+            Convention::AnyPart => Some(trip),
+            Convention::Midnights | Convention::ExcludingTransit if exit == trip.entry => None,
+            Convention::Midnights => {
+                trip.exit = Some(exit - Days::new(1));
+                Some(trip)
+            }
+            Convention::ExcludingTransit => Some(trip),
+        }
+    }
+
+    /// Cuts the trips to the dates on which the rules apply, dropping the trips outside them. An
+    /// open trip is closed on the last effective day.
+    ///
+    /// If the regime lists its member countries, trips to other countries are dropped and trips to
+    /// a member are cut to the days since it joined the zone. Trips without a country are counted.
+    /// Trips with legs are split into a trip per leg first.
+    pub fn applicable_trips(&self, trips: &[Trip]) -> Vec<Trip> {
+        trips
+            .iter()
+            .flat_map(Trip::split_legs)
+            .filter_map(|mut trip| {
+                if let (Some(country), false) = (&trip.info.country, self.members.is_empty()) {
+                    let since = self.member(country)?.since(trip.info.border);
+                    if trip.exit.is_some_and(|exit| exit < since) {
+                        return None;
+                    }
+                    trip.entry = trip.entry.max(since);
+                }
+                if let Some(from) = self.effective_from {
+                    if trip.exit.is_some_and(|exit| exit < from) {
+                        return None;
+                    }
+                    trip.entry = trip.entry.max(from);
+                }
+                if let Some(until) = self.effective_until {
+                    if trip.entry > until {
+                        return None;
+                    }
+                    trip.exit = Some(trip.exit.map_or(until, |exit| exit.min(until)));
+                }
+                Some(trip)
+            })
+            .collect()
+    }
+}
+
+impl Regime {
+    /// Counts the days spent in the zone of the regime in the control period ending on `date`.
+    /// Only the legs of the trips to the countries of the regime are counted, and a day of
+    /// travelling between two regimes counts in both.
+    pub fn usage(
+        &self,
+        trips: &[Trip],
+        permits: &[Permit],
+        date: NaiveDate,
+    ) -> Result<RegimeUsage> {
+        let (trips, excluded) = self.counted_trips(trips, permits);
+        let stays = DateIntervalVec::from_trips(&trips, date);
+        let check = self.engine().check(&stays, date)?;
+        Ok(RegimeUsage {
+            regime: self.name.clone(),
+            excluded: clip_exclusions(excluded, check.control_period),
+            check,
+        })
+    }
+}
+
+/// Counts the days used under each of the `regimes` in the control periods ending on `date`. Trips
+/// and legs without a country could be in any of the zones, so when several regimes are counted
+/// they are counted under the `selected` regime only, and not at all if it is not one of them.
+pub fn usages(
+    regimes: &[Regime],
+    selected: &Regime,
+    trips: &[Trip],
+    permits: &[Permit],
+    date: NaiveDate,
+) -> Result<Vec<RegimeUsage>> {
+    let all_trips: Vec<_> = trips.iter().flat_map(Trip::split_legs).collect();
+    let labelled: Vec<_> = all_trips
+        .iter()
+        .filter(|trip| trip.info.country.is_some())
+        .cloned()
+        .collect();
+    regimes
+        .iter()
+        .map(|regime| {
+            let trips = if regimes.len() == 1 || regime.name == selected.name {
+                &all_trips
+            } else {
+                &labelled
+            };
+            regime.usage(trips, permits, date)
+        })
+        .collect()
+}
+
+/// Number of trips and legs without a country.
+pub fn num_unlabelled(trips: &[Trip]) -> usize {
+    trips
+        .iter()
+        .flat_map(Trip::split_legs)
+        .filter(|trip| trip.info.country.is_none())
+        .count()
+}
+
+/// Writes the usage of every regime as an aligned text table.
+pub fn write_usage_table<W: Write>(mut w: W, usages: &[RegimeUsage]) -> Result<()> {
+    let width = usages
+        .iter()
+        .map(|u| u.regime.chars().count())
+        .chain(Some(6))
+        .max()
+        .unwrap_or_default();
+    writeln!(
+        w,
+        "{:<width$}  {:>4}  {:>9}  {:<7}",
+        "regime", "used", "remaining", "verdict"
+    )?;
+    for usage in usages {
+        writeln!(
+            w,
+            "{:<width$}  {:>4}  {:>9}  {}",
+            usage.regime,
+            usage.check.days_used,
+            usage.check.days_remaining,
+            match usage.check.verdict {
+                Verdict::Within => "within",
+                Verdict::Exceeds => "exceeds",
+            }
+        )?;
+    }
+    Ok(())
+}
+
+impl Default for Regime {
+    fn default() -> Self {
+        Self::builtin().remove(0)
+    }
+}
+
+impl FromStr for Regime {
+    type Err = anyhow::Error;
+
+    /// Finds a built-in regime by name, ignoring case.
+    fn from_str(s: &str) -> Result<Self> {
+        let regimes = Self::builtin();
+        if let Some(regime) = regimes.iter().find(|r| r.name.eq_ignore_ascii_case(s)) {
+            return Ok(regime.clone());
+        }
+        let names: Vec<_> = regimes.iter().map(|r| r.name.as_str()).collect();
+        anyhow::bail!("Unknown regime '{s}', choose one of: {}", names.join(", "))
+    }
+}
@@ -0,0 +1,403 @@
+//! Self-contained HTML report with SVG charts.
+
+use crate::{
+    DateInterval, DateIntervalVec, Engine, PlannedTrip, TimelineDay, Trip, Verdict, WindowCheck,
+};
+use anyhow::Result;
+use chrono::{Datelike, Days, NaiveDate};
+use serde::Serialize;
+use std::io::Write;
+
+/// Everything shown in the report: the trip history, the allowance on the report date, the
+/// planned trips and the allowance on every day of the chart.
+#[derive(Debug, Clone, Serialize)]
+pub struct TravelReport {
+    pub date: NaiveDate,
+    pub rule: Engine,
+    pub check: WindowCheck,
+    pub trips: Vec<Trip>,
+    pub planned: Vec<PlannedTrip>,
+    /// Allowance on every day from the first trip or the start of the control period, whichever is
+    /// earlier, to the report date or the end of the last planned trip, whichever is later.
+    pub usage: Vec<TimelineDay>,
+}
+
+impl Engine {
+    /// Makes the report on `date` for the trip history `trips` and the trips in `planned`. Open
+    /// trips are counted until `date`.
+    pub fn travel_report(
+        &self,
+        trips: &[Trip],
+        planned: &[Trip],
+        date: NaiveDate,
+    ) -> Result<TravelReport> {
+        let history = DateIntervalVec::from_trips(trips, date);
+        let check = self.check(&history, date)?;
+        let planned = self.check_planned(&history, planned)?;
+
+        let mut stays = history;
+        let mut start = check.control_period.start();
+        let mut end = date;
+        for trip in trips {
+            start = start.min(trip.entry);
+        }
+        for p in &planned {
+            if let Some(di) = p.trip.interval(NaiveDate::MAX) {
+                stays = stays.with(di);
+                end = end.max(di.end());
+            }
+        }
+        let usage = self.timeline(&stays, DateInterval::new(start, end)?)?;
+
+        Ok(TravelReport {
+            date,
+            rule: *self,
+            check,
+            trips: trips.to_vec(),
+            planned,
+            usage,
+        })
+    }
+}
+
+/// Width of the charts in pixels.
+const WIDTH: f64 = 960.0;
+/// Left margin of the charts for the axis labels.
+const MARGIN: f64 = 40.0;
+const TIMELINE_HEIGHT: f64 = 110.0;
+const USAGE_HEIGHT: f64 = 260.0;
+
+const STYLE: &str = "\
+body { font-family: sans-serif; margin: 2em; color: #222; }
+h1 { font-size: 1.5em; }
+h2 { font-size: 1.2em; margin-top: 1.5em; }
+table { border-collapse: collapse; }
+th, td { border: 1px solid #ccc; padding: 0.25em 0.75em; text-align: left; }
+th { background: #f0f0f0; }
+td.num { text-align: right; }
+.exceeds { color: #b00; font-weight: bold; }
+svg text { font-size: 11px; fill: #444; }";
+
+/// Writes the report as a single HTML document without external assets.
+pub fn write_html<W: Write>(mut w: W, report: &TravelReport) -> Result<()> {
+    let check = &report.check;
+    writeln!(w, "<!DOCTYPE html>")?;
+    writeln!(w, "<html lang=\"en\">")?;
+    writeln!(w, "<head>")?;
+    writeln!(w, "<meta charset=\"utf-8\">")?;
+    writeln!(w, "<title>Travel report on {}</title>", report.date)?;
+    writeln!(w, "<style>\n{STYLE}\n</style>")?;
+    writeln!(w, "</head>")?;
+    writeln!(w, "<body>")?;
+    writeln!(w, "<h1>Travel report on {}</h1>", report.date)?;
+    writeln!(w, "<p>Rule: {}.</p>", report.rule)?;
+
+    writeln!(w, "<h2>Control period</h2>")?;
+    writeln!(w, "<table>")?;
+    writeln!(
+        w,
+        "<tr><th>Control period</th><td>{}</td></tr>",
+        check.control_period
+    )?;
+    writeln!(
+        w,
+        "<tr><th>Days used</th><td class=\"num\">{}</td></tr>",
+        check.days_used
+    )?;
+    writeln!(
+        w,
+        "<tr><th>Days remaining</th><td class=\"num\">{}</td></tr>",
+        check.days_remaining
+    )?;
+    writeln!(
+        w,
+        "<tr><th>Verdict</th><td{}>Spent days {} the allowed number</td></tr>",
+        if check.verdict == Verdict::Exceeds {
+            " class=\"exceeds\""
+        } else {
+            ""
+        },
+        check.verdict
+    )?;
+    writeln!(w, "</table>")?;
+
+    if let Some(range) = usage_range(&report.usage) {
+        writeln!(w, "<h2>Trips</h2>")?;
+        write_timeline_svg(&mut w, report, range)?;
+        writeln!(w, "<h2>Days used in the control period</h2>")?;
+        write_usage_svg(&mut w, report, range)?;
+    }
+
+    writeln!(w, "<h2>Trip history</h2>")?;
+    writeln!(w, "<table>")?;
+    writeln!(
+        w,
+        "<tr><th>#</th><th>Entry</th><th>Exit</th><th>Days</th><th>Details</th></tr>"
+    )?;
+    for (n, trip) in report.trips.iter().enumerate() {
+        let exit = match trip.exit {
+            Some(exit) => exit.to_string(),
+            None => "still in the zone".to_string(),
+        };
+        let days = trip.interval(report.date).map_or(0, |di| di.abs_num_days());
+        writeln!(
+            w,
+            "<tr><td class=\"num\">{}</td><td>{}</td><td>{exit}</td><td class=\"num\">{days}</td>\
+             <td>{}</td></tr>",
+            n + 1,
+            trip.entry,
+            escape(&trip.info.to_string())
+        )?;
+    }
+    writeln!(w, "</table>")?;
+
+    if !report.planned.is_empty() {
+        writeln!(w, "<h2>Planned trips</h2>")?;
+        writeln!(w, "<table>")?;
+        writeln!(
+            w,
+            "<tr><th>#</th><th>Entry</th><th>Exit</th><th>Days</th><th>Status</th></tr>"
+        )?;
+        for (n, p) in report.planned.iter().enumerate() {
+            let Some(di) = p.trip.interval(NaiveDate::MAX) else {
+                continue;
+            };
+            let status = match &p.breach {
+                None => "allowed".to_string(),
+                Some(breach) => {
+                    let latest_exit = match breach.latest_exit {
+                        Some(date) => format!(", leave by {date}"),
+                        None => ", entering is not allowed".to_string(),
+                    };
+                    format!(
+                        "exceeds the allowance from {} by up to {} days{latest_exit}",
+                        breach.first_day, breach.days_over
+                    )
+                }
+            };
+            writeln!(
+                w,
+                "<tr><td class=\"num\">{}</td><td>{}</td><td>{}</td><td class=\"num\">{}</td>\
+                 <td{}>{status}</td></tr>",
+                n + 1,
+                di.start(),
+                di.end(),
+                di.abs_num_days(),
+                if p.breach.is_some() {
+                    " class=\"exceeds\""
+                } else {
+                    ""
+                }
+            )?;
+        }
+        writeln!(w, "</table>")?;
+    }
+
+    writeln!(w, "</body>")?;
+    writeln!(w, "</html>")?;
+    Ok(())
+}
+
+/// Date range of the charts.
+fn usage_range(usage: &[TimelineDay]) -> Option<DateInterval> {
+    let first = usage.first()?;
+    let last = usage.last()?;
+    DateInterval::new(first.date, last.date).ok()
+}
+
+/// Horizontal position of the start of `date` in a chart of `range`.
+fn x(range: DateInterval, date: NaiveDate) -> f64 {
+    let day_width = (WIDTH - MARGIN) / range.abs_num_days() as f64;
+    MARGIN + (date - range.start()).num_days() as f64 * day_width
+}
+
+/// Writes month or year ticks along the bottom of a chart of `range` and `height`.
+fn write_ticks<W: Write>(w: &mut W, range: DateInterval, height: f64) -> Result<()> {
+    // Label every month on charts of up to two years and only January on longer ones.
+    let monthly = range.abs_num_days() <= 2 * 366;
+    let mut date = range.start();
+    while date <= range.end() {
+        if date.day() == 1 && (monthly || date.month() == 1) {
+            let x = x(range, date);
+            let label = if date.month() == 1 {
+                date.format("%Y").to_string()
+            } else {
+                date.format("%b").to_string()
+            };
+            writeln!(
+                w,
+                "<line x1=\"{x:.1}\" y1=\"0\" x2=\"{x:.1}\" y2=\"{:.1}\" stroke=\"#eee\"/>",
+                height - 15.0
+            )?;
+            writeln!(
+                w,
+                "<text x=\"{x:.1}\" y=\"{:.1}\" text-anchor=\"middle\">{label}</text>",
+                height - 3.0
+            )?;
+        }
+        date = date + Days::new(1);
+    }
+    Ok(())
+}
+
+/// Writes a marker of the report date.
+fn write_today<W: Write>(
+    w: &mut W,
+    report: &TravelReport,
+    range: DateInterval,
+    height: f64,
+) -> Result<()> {
+    let x = x(range, report.date)
+        + (x(range, report.date + Days::new(1)) - x(range, report.date)) / 2.0;
+    writeln!(
+        w,
+        "<line x1=\"{x:.1}\" y1=\"0\" x2=\"{x:.1}\" y2=\"{:.1}\" stroke=\"#222\" \
+         stroke-dasharray=\"2,2\"><title>{}</title></line>",
+        height - 15.0,
+        report.date
+    )?;
+    Ok(())
+}
+
+/// Writes the trips as bars on a timeline, with the control period ending on the report date in
+/// the background.
+fn write_timeline_svg<W: Write>(
+    w: &mut W,
+    report: &TravelReport,
+    range: DateInterval,
+) -> Result<()> {
+    let height = TIMELINE_HEIGHT;
+    writeln!(
+        w,
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{WIDTH}\" height=\"{height}\" \
+         viewBox=\"0 0 {WIDTH} {height}\">"
+    )?;
+    let period = report.check.control_period;
+    let (x0, x1) = (
+        x(range, period.start()),
+        x(range, period.end() + Days::new(1)),
+    );
+    writeln!(
+        w,
+        "<rect x=\"{x0:.1}\" y=\"0\" width=\"{:.1}\" height=\"{:.1}\" fill=\"#eef4ff\">\
+         <title>Control period {period}</title></rect>",
+        x1 - x0,
+        height - 15.0
+    )?;
+    write_ticks(w, range, height)?;
+    writeln!(w, "<text x=\"0\" y=\"32\">Trips</text>")?;
+    writeln!(w, "<text x=\"0\" y=\"72\">Plans</text>")?;
+
+    let mut bar = |di: DateInterval, y: f64, fill: &str, title: String| -> Result<()> {
+        let (x0, x1) = (x(range, di.start()), x(range, di.end() + Days::new(1)));
+        writeln!(
+            w,
+            "<rect x=\"{x0:.1}\" y=\"{y}\" width=\"{:.1}\" height=\"24\" fill=\"{fill}\">\
+             <title>{}</title></rect>",
+            (x1 - x0).max(1.0),
+            escape(&title)
+        )?;
+        Ok(())
+    };
+    for trip in &report.trips {
+        if let Some(di) = trip.interval(report.date) {
+            let title = if trip.info.is_empty() {
+                format!("{di}")
+            } else {
+                format!("{di} ({})", trip.info)
+            };
+            bar(di, 15.0, "#3366cc", title)?;
+        }
+    }
+    for p in &report.planned {
+        if let Some(di) = p.trip.interval(NaiveDate::MAX) {
+            let fill = if p.breach.is_some() {
+                "#cc3333"
+            } else {
+                "#33aa55"
+            };
+            bar(di, 55.0, fill, format!("Planned {di}"))?;
+        }
+    }
+    write_today(w, report, range, height)?;
+    writeln!(w, "</svg>")?;
+    Ok(())
+}
+
+/// Writes the days used in the control period ending on every day as a line chart, with the
+/// allowance as a horizontal line.
+fn write_usage_svg<W: Write>(w: &mut W, report: &TravelReport, range: DateInterval) -> Result<()> {
+    let height = USAGE_HEIGHT;
+    let plot_height = height - 25.0;
+    let allowed = report.rule.allowed().unwrap_or(report.rule.limit());
+    let max_used = report
+        .usage
+        .iter()
+        .map(|day| day.days_used)
+        .max()
+        .unwrap_or(0);
+    let max = max_used.max(allowed).max(1) as f64 * 1.1;
+    let y = |days: usize| plot_height - days as f64 / max * (plot_height - 10.0);
+
+    writeln!(
+        w,
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{WIDTH}\" height=\"{height}\" \
+         viewBox=\"0 0 {WIDTH} {height}\">"
+    )?;
+    write_ticks(w, range, height)?;
+    let y_allowed = y(allowed);
+    writeln!(
+        w,
+        "<line x1=\"{MARGIN}\" y1=\"{y_allowed:.1}\" x2=\"{WIDTH}\" y2=\"{y_allowed:.1}\" \
+         stroke=\"#cc3333\" stroke-dasharray=\"6,3\"/>"
+    )?;
+    writeln!(
+        w,
+        "<text x=\"0\" y=\"{:.1}\">{allowed}</text>",
+        y_allowed + 4.0
+    )?;
+    writeln!(w, "<text x=\"0\" y=\"{:.1}\">0</text>", y(0) + 4.0)?;
+
+    // Each day is drawn as a step from its start to its end.
+    let mut points = Vec::with_capacity(report.usage.len() * 2);
+    for day in &report.usage {
+        let y = y(day.days_used);
+        points.push(format!("{:.1},{y:.1}", x(range, day.date)));
+        points.push(format!("{:.1},{y:.1}", x(range, day.date + Days::new(1))));
+    }
+    writeln!(
+        w,
+        "<polyline points=\"{}\" fill=\"none\" stroke=\"#3366cc\" stroke-width=\"1.5\"/>",
+        points.join(" ")
+    )?;
+    for day in report.usage.iter().filter(|day| day.days_used > allowed) {
+        let (x0, x1) = (x(range, day.date), x(range, day.date + Days::new(1)));
+        writeln!(
+            w,
+            "<rect x=\"{x0:.1}\" y=\"{:.1}\" width=\"{:.1}\" height=\"3\" fill=\"#cc3333\">\
+             <title>{}: {} days used</title></rect>",
+            plot_height + 1.0,
+            (x1 - x0).max(1.0),
+            day.date,
+            day.days_used
+        )?;
+    }
+    write_today(w, report, range, height)?;
+    writeln!(w, "</svg>")?;
+    Ok(())
+}
+
+/// Escapes the characters that have a special meaning in HTML.
+fn escape(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
@@ -0,0 +1,100 @@
+//! US substantial presence test.
+
+use crate::{Convention, DateInterval, DateIntervalVec, TaxRule, Trip, YearStart};
+use anyhow::Result;
+use chrono::NaiveDate;
+use serde::Serialize;
+
+/// Weighted days in the US that meet the test.
+pub const THRESHOLD_DAYS: usize = 183;
+/// Days in the US in the current year needed to meet the test.
+pub const MIN_CURRENT_DAYS: usize = 31;
+
+/// Outcome of the substantial presence test for a calendar year.
+#[derive(Debug, Clone, Serialize)]
+pub struct PresenceTest {
+    pub year: i32,
+    /// Days in the US in the year, without the exempt days.
+    pub current_days: usize,
+    /// Days in the US in the year before, a third of which count.
+    pub first_preceding_days: usize,
+    /// Days in the US two years before, a sixth of which count.
+    pub second_preceding_days: usize,
+    /// Days in the US in the three years not counted because they were exempt.
+    pub exempt_days: usize,
+    /// Days in the year plus a third of the days in the year before and a sixth of the days two
+    /// years before.
+    pub weighted_days: f64,
+    pub met: bool,
+    /// More days in the US in the year that would meet the test, `None` if it is met.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub days_to_meet: Option<usize>,
+}
+
+/// Rule counting the days in the US: any part of a day in the US counts, except days in transit
+/// between two other countries, taken to be trips and legs starting and ending on the same day.
+pub fn rule() -> TaxRule {
+    TaxRule {
+        country: Some("US".to_string()),
+        year_start: YearStart::JANUARY,
+        convention: Convention::ExcludingTransit,
+        threshold: THRESHOLD_DAYS,
+    }
+}
+
+/// Applies the substantial presence test to the calendar `year`. Only the trips and legs with the
+/// country `US`, `USA` or `United States` are counted, see [`TaxRule::trips`], and the days in the
+/// `exempt` periods, such as those spent as a teacher, trainee or student, are left out. Open trips
+/// last until `until`.
+pub fn presence_test(
+    trips: &[Trip],
+    year: i32,
+    exempt: &[DateInterval],
+    until: NaiveDate,
+) -> Result<PresenceTest> {
+    let days = rule().days(trips, until);
+    let exempt = DateIntervalVec::from(exempt.to_vec());
+    // Days in the US in the year starting in `year`, and the exempt days among them.
+    let count = |year| -> Result<(usize, usize)> {
+        let period = YearStart::JANUARY.period(year)?;
+        let (mut present, mut excluded) = (0, 0);
+        for date in period
+            .start()
+            .iter_days()
+            .take_while(|&d| d <= period.end())
+        {
+            if !days.contains(date) {
+                continue;
+            }
+            if exempt.contains(date) {
+                excluded += 1;
+            } else {
+                present += 1;
+            }
+        }
+        Ok((present, excluded))
+    };
+    let (current, current_exempt) = count(year)?;
+    let (first, first_exempt) = count(year - 1)?;
+    let (second, second_exempt) = count(year - 2)?;
+
+    // Compared in sixths of a day to avoid rounding.
+    let sixths = 6 * current + 2 * first + second;
+    let met = sixths >= 6 * THRESHOLD_DAYS && current >= MIN_CURRENT_DAYS;
+    let days_to_meet = (!met).then(|| {
+        (6 * THRESHOLD_DAYS)
+            .saturating_sub(sixths)
+            .div_ceil(6)
+            .max(MIN_CURRENT_DAYS.saturating_sub(current))
+    });
+    Ok(PresenceTest {
+        year,
+        current_days: current,
+        first_preceding_days: first,
+        second_preceding_days: second,
+        exempt_days: current_exempt + first_exempt + second_exempt,
+        weighted_days: sixths as f64 / 6.0,
+        met,
+        days_to_meet,
+    })
+}
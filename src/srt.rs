@@ -0,0 +1,217 @@
+//! UK statutory residence test: days spent in the UK in a tax year and the tax residence they lead
+//! to.
+
+use crate::membership::country_code;
+use crate::tax::YearStart;
+use crate::{DateInterval, DateIntervalVec, Trip};
+use anyhow::Result;
+use chrono::NaiveDate;
+use serde::Serialize;
+use std::fmt;
+use std::str::FromStr;
+
+/// Tie to the UK counted by the sufficient ties test.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Tie {
+    /// Spouse, civil partner or minor child resident in the UK.
+    Family,
+    /// Accommodation in the UK available for 91 days or more and used in the tax year.
+    Accommodation,
+    /// Working in the UK for more than three hours on 40 days or more.
+    Work,
+    /// More than 90 days in the UK in either of the previous two tax years.
+    #[serde(rename = "90-day")]
+    NinetyDay,
+    /// More midnights in the UK than in any other single country. Only counted for those who were
+    /// resident in one of the previous three tax years.
+    Country,
+}
+
+impl fmt::Display for Tie {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Family => write!(f, "family"),
+            Self::Accommodation => write!(f, "accommodation"),
+            Self::Work => write!(f, "work"),
+            Self::NinetyDay => write!(f, "90-day"),
+            Self::Country => write!(f, "country"),
+        }
+    }
+}
+
+impl FromStr for Tie {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "family" => Ok(Self::Family),
+            "accommodation" => Ok(Self::Accommodation),
+            "work" => Ok(Self::Work),
+            "90-day" | "ninety-day" => Ok(Self::NinetyDay),
+            "country" => Ok(Self::Country),
+            _ => anyhow::bail!(
+                "Unknown tie '{s}', expected family, accommodation, work, 90-day or country"
+            ),
+        }
+    }
+}
+
+/// Part of the statutory residence test that decided the residence.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Test {
+    /// Fewer than 16 days in the UK after being resident in one of the previous three tax years.
+    FirstAutomaticOverseas,
+    /// Fewer than 46 days in the UK without being resident in the previous three tax years.
+    SecondAutomaticOverseas,
+    /// Fewer than 91 days in the UK while working full time abroad.
+    ThirdAutomaticOverseas,
+    /// 183 days or more in the UK.
+    FirstAutomaticUk,
+    /// Enough ties to the UK for the number of days spent there.
+    SufficientTies,
+}
+
+impl fmt::Display for Test {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::FirstAutomaticOverseas => write!(f, "the first automatic overseas test"),
+            Self::SecondAutomaticOverseas => write!(f, "the second automatic overseas test"),
+            Self::ThirdAutomaticOverseas => write!(f, "the third automatic overseas test"),
+            Self::FirstAutomaticUk => write!(f, "the first automatic UK test"),
+            Self::SufficientTies => write!(f, "the sufficient ties test"),
+        }
+    }
+}
+
+/// Facts about the traveller that the trips do not show.
+#[derive(Debug, Clone, Default)]
+pub struct Declaration {
+    /// Ties to the UK. The 90-day tie is also found from the trips.
+    pub ties: Vec<Tie>,
+    /// Resident in the UK in one or more of the previous three tax years.
+    pub resident_before: bool,
+    /// Working full time abroad, and for more than three hours in the UK on fewer than 31 days.
+    pub works_abroad: bool,
+}
+
+/// Outcome of the statutory residence test for a tax year.
+#[derive(Debug, Clone, Serialize)]
+pub struct ResidenceTest {
+    /// Name of the tax year, such as `2024-25`.
+    pub tax_year: String,
+    pub period: DateInterval,
+    /// Days in the UK at midnight.
+    pub uk_days: usize,
+    pub resident_before: bool,
+    /// Ties counted by the sufficient ties test.
+    pub ties: Vec<Tie>,
+    /// Ties needed to be resident, if the sufficient ties test applies.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ties_needed: Option<usize>,
+    pub test: Test,
+    pub resident: bool,
+}
+
+/// Tax year starting on 6 April of `year` and ending on 5 April of the next year.
+pub fn tax_year(year: i32) -> Result<DateInterval> {
+    YearStart::UK.period(year)
+}
+
+/// Year in which the tax year containing `date` starts.
+pub fn tax_year_of(date: NaiveDate) -> i32 {
+    YearStart::UK.year_of(date)
+}
+
+/// Trips to the UK, including the legs in the UK of longer trips. Trips without a country are not
+/// counted.
+pub fn uk_trips(trips: &[Trip]) -> Vec<Trip> {
+    trips
+        .iter()
+        .flat_map(Trip::split_legs)
+        .filter(|trip| {
+            trip.info
+                .country
+                .as_deref()
+                .is_some_and(|c| country_code(c) == "GB")
+        })
+        .collect()
+}
+
+/// Days in `period` at the end of which the traveller was in the UK. The day of leaving is not
+/// counted, and open trips last until `until`.
+pub fn uk_days(trips: &[Trip], period: DateInterval, until: NaiveDate) -> usize {
+    DateIntervalVec::from_trips(trips, until)
+        .midnights()
+        .clip(period)
+        .num_spent_days()
+}
+
+/// Ties needed to be resident with `uk_days` in the UK, or `None` outside the range of days
+/// decided by the sufficient ties test.
+pub fn ties_needed(uk_days: usize, resident_before: bool) -> Option<usize> {
+    match (uk_days, resident_before) {
+        (16..=45, true) => Some(4),
+        (46..=90, true) => Some(3),
+        (91..=120, true) => Some(2),
+        (121..=182, true) => Some(1),
+        (46..=90, false) => Some(4),
+        (91..=120, false) => Some(3),
+        (121..=182, false) => Some(2),
+        _ => None,
+    }
+}
+
+/// Applies the automatic overseas tests, the first automatic UK test and the sufficient ties test
+/// to the tax year starting in `year`. Only the trips to the UK are counted, see [`uk_trips`].
+///
+/// The automatic UK tests about homes and full-time work in the UK, split years and the deeming
+/// rule for days without a midnight in the UK are not applied.
+pub fn residence_test(
+    trips: &[Trip],
+    year: i32,
+    declaration: &Declaration,
+    until: NaiveDate,
+) -> Result<ResidenceTest> {
+    let trips = uk_trips(trips);
+    let mut ties = declaration.ties.clone();
+    for previous in [year - 1, year - 2] {
+        if uk_days(&trips, tax_year(previous)?, until) > 90 {
+            ties.push(Tie::NinetyDay);
+        }
+    }
+    let period = tax_year(year)?;
+    let uk_days = uk_days(&trips, period, until);
+    let resident_before = declaration.resident_before;
+    if !resident_before {
+        ties.retain(|&tie| tie != Tie::Country);
+    }
+    ties.sort();
+    ties.dedup();
+
+    let ties_needed = ties_needed(uk_days, resident_before);
+    let (test, resident) = if resident_before && uk_days < 16 {
+        (Test::FirstAutomaticOverseas, false)
+    } else if !resident_before && uk_days < 46 {
+        (Test::SecondAutomaticOverseas, false)
+    } else if declaration.works_abroad && uk_days < 91 {
+        (Test::ThirdAutomaticOverseas, false)
+    } else if uk_days >= 183 {
+        (Test::FirstAutomaticUk, true)
+    } else {
+        let needed = ties_needed.expect("days between the automatic tests");
+        (Test::SufficientTies, ties.len() >= needed)
+    };
+
+    Ok(ResidenceTest {
+        tax_year: YearStart::UK.name(year),
+        period,
+        uk_days,
+        resident_before,
+        ties,
+        ties_needed: ties_needed.filter(|_| test == Test::SufficientTies),
+        test,
+        resident,
+    })
+}
@@ -0,0 +1,283 @@
+//! Days of presence in a country per tax year, for the "183 days in a tax year" residence rules.
+
+use crate::membership::country_code;
+use crate::{DateInterval, DateIntervalVec, Trip};
+use anyhow::{Context, Result};
+use chrono::{Datelike, Days, NaiveDate};
+use serde::{Serialize, Serializer};
+use std::fmt;
+use std::io::Write;
+use std::ops::RangeInclusive;
+use std::str::FromStr;
+
+/// Way of counting a day as a day of presence.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Convention {
+    /// Any part of a day spent in the country, including the days of arriving and leaving.
+    AnyPart,
+    /// Days at the end of which the traveller was in the country, so the day of leaving is not
+    /// counted.
+    Midnights,
+    /// Any part of a day, except trips and legs that start and end on the same day, which are taken
+    /// to be transit.
+    ExcludingTransit,
+}
+
+impl fmt::Display for Convention {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::AnyPart => write!(f, "any part of a day"),
+            Self::Midnights => write!(f, "midnights"),
+            Self::ExcludingTransit => write!(f, "any part of a day excluding transit"),
+        }
+    }
+}
+
+impl FromStr for Convention {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "any-part" => Ok(Self::AnyPart),
+            "midnights" => Ok(Self::Midnights),
+            "excluding-transit" => Ok(Self::ExcludingTransit),
+            _ => anyhow::bail!(
+                "Unknown convention '{s}', expected any-part, midnights or excluding-transit"
+            ),
+        }
+    }
+}
+
+/// Month and day on which a tax year starts.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct YearStart {
+    month: u32,
+    day: u32,
+}
+
+impl YearStart {
+    /// Calendar year.
+    pub const JANUARY: Self = Self { month: 1, day: 1 };
+    /// UK tax year, starting on 6 April.
+    pub const UK: Self = Self { month: 4, day: 6 };
+
+    /// Makes a year start, checking that the day exists in every year.
+    pub fn new(month: u32, day: u32) -> Result<Self> {
+        // 29 February is missing in most years.
+        NaiveDate::from_ymd_opt(2023, month, day)
+            .with_context(|| format!("Bad start of the tax year {month:02}-{day:02}"))?;
+        Ok(Self { month, day })
+    }
+
+    /// Tax year starting in `year`.
+    pub fn period(&self, year: i32) -> Result<DateInterval> {
+        let start = NaiveDate::from_ymd_opt(year, self.month, self.day);
+        let next = NaiveDate::from_ymd_opt(year + 1, self.month, self.day);
+        start
+            .zip(next)
+            .and_then(|(start, next)| DateInterval::new(start, next - Days::new(1)).ok())
+            .with_context(|| format!("Bad tax year {year}"))
+    }
+
+    /// Year in which the tax year containing `date` starts.
+    pub fn year_of(&self, date: NaiveDate) -> i32 {
+        if (date.month(), date.day()) < (self.month, self.day) {
+            date.year() - 1
+        } else {
+            date.year()
+        }
+    }
+
+    /// Name of the tax year starting in `year`: the year for calendar years, such as `2024`, or
+    /// both years otherwise, such as `2024-25`.
+    pub fn name(&self, year: i32) -> String {
+        if *self == Self::JANUARY {
+            year.to_string()
+        } else {
+            format!("{year}-{:02}", (year + 1) % 100)
+        }
+    }
+}
+
+impl Default for YearStart {
+    fn default() -> Self {
+        Self::JANUARY
+    }
+}
+
+impl fmt::Display for YearStart {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:02}-{:02}", self.month, self.day)
+    }
+}
+
+impl Serialize for YearStart {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl FromStr for YearStart {
+    type Err = anyhow::Error;
+
+    /// Parses the start of the tax year written as `MM-DD`, for example `04-06`.
+    fn from_str(s: &str) -> Result<Self> {
+        let (month, day) = s
+            .trim()
+            .split_once('-')
+            .with_context(|| format!("Expected MM-DD, got '{s}'"))?;
+        let month = month
+            .parse()
+            .with_context(|| format!("Invalid month '{month}'"))?;
+        let day = day
+            .parse()
+            .with_context(|| format!("Invalid day '{day}'"))?;
+        Self::new(month, day)
+    }
+}
+
+/// Rule counting the days of presence in a country per tax year.
+#[derive(Debug, Clone, Serialize)]
+pub struct TaxRule {
+    /// Country whose days are counted, by code or name. Only the trips and legs with the country
+    /// are counted, or every trip if `None`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub country: Option<String>,
+    pub year_start: YearStart,
+    pub convention: Convention,
+    /// Days in a tax year that make the traveller resident.
+    pub threshold: usize,
+}
+
+impl Default for TaxRule {
+    fn default() -> Self {
+        Self {
+            country: None,
+            year_start: YearStart::default(),
+            convention: Convention::AnyPart,
+            threshold: 183,
+        }
+    }
+}
+
+/// Days counted in one tax year.
+#[derive(Debug, Clone, Serialize)]
+pub struct TaxYearDays {
+    /// Name of the tax year, such as `2024` or `2024-25`.
+    pub tax_year: String,
+    pub period: DateInterval,
+    /// Days counted on the trips.
+    pub days: usize,
+    /// Days counted on the trips and the planned trips.
+    pub planned_days: usize,
+    /// Day on which the threshold is reached, counting the planned trips.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub threshold_reached: Option<NaiveDate>,
+}
+
+impl TaxRule {
+    /// Trips to the country, with the legs of longer trips in other countries left out. Trips
+    /// without a country are not counted.
+    pub fn trips(&self, trips: &[Trip]) -> Vec<Trip> {
+        let Some(country) = &self.country else {
+            return trips.to_vec();
+        };
+        let code = country_code(country);
+        trips
+            .iter()
+            .flat_map(Trip::split_legs)
+            .filter(|trip| {
+                trip.info
+                    .country
+                    .as_deref()
+                    .is_some_and(|c| country_code(c) == code)
+            })
+            .collect()
+    }
+
+    /// Days counted on the trips under the convention. Open trips last until `until`.
+    pub fn days(&self, trips: &[Trip], until: NaiveDate) -> DateIntervalVec {
+        let stays = DateIntervalVec::from_trips(&self.trips(trips), until);
+        match self.convention {
+            Convention::AnyPart => stays,
+            Convention::Midnights => stays.midnights(),
+            Convention::ExcludingTransit => stays
+                .as_slice()
+                .iter()
+                .copied()
+                .filter(|di| di.abs_num_days() > 1)
+                .collect::<Vec<_>>()
+                .into(),
+        }
+    }
+
+    /// Counts the days in each tax year starting in `years`, without and with the `planned` trips.
+    /// Planned trips without a country are taken to be trips to the country. Open trips last until
+    /// `until`.
+    pub fn count(
+        &self,
+        trips: &[Trip],
+        planned: &[Trip],
+        years: RangeInclusive<i32>,
+        until: NaiveDate,
+    ) -> Result<Vec<TaxYearDays>> {
+        let days = self.days(trips, until);
+        let planned = planned.iter().map(|trip| {
+            let mut trip = trip.clone();
+            if trip.info.country.is_none() {
+                trip.info.country.clone_from(&self.country);
+            }
+            trip
+        });
+        let all_trips: Vec<_> = trips.iter().cloned().chain(planned).collect();
+        let planned_days = self.days(&all_trips, until);
+        years
+            .map(|year| {
+                let period = self.year_start.period(year)?;
+                let threshold_reached = period
+                    .start()
+                    .iter_days()
+                    .take_while(|&date| date <= period.end())
+                    .filter(|&date| planned_days.contains(date))
+                    .nth(self.threshold.saturating_sub(1));
+                Ok(TaxYearDays {
+                    tax_year: self.year_start.name(year),
+                    period,
+                    days: days.clip(period).num_spent_days(),
+                    planned_days: planned_days.clip(period).num_spent_days(),
+                    threshold_reached,
+                })
+            })
+            .collect()
+    }
+}
+
+/// Writes the days of every tax year as an aligned text table.
+pub fn write_table<W: Write>(mut w: W, years: &[TaxYearDays]) -> Result<()> {
+    let width = years
+        .iter()
+        .map(|y| y.tax_year.chars().count())
+        .chain(Some(4))
+        .max()
+        .unwrap_or_default();
+    writeln!(
+        w,
+        "{:<width$}  {:<10}  {:<10}  {:>4}  {:>7}  threshold",
+        "year", "start", "end", "days", "planned"
+    )?;
+    for year in years {
+        writeln!(
+            w,
+            "{:<width$}  {:<10}  {:<10}  {:>4}  {:>7}  {}",
+            year.tax_year,
+            year.period.start(),
+            year.period.end(),
+            year.days,
+            year.planned_days,
+            year.threshold_reached
+                .map_or_else(|| "-".to_string(), |date| date.to_string())
+        )?;
+    }
+    Ok(())
+}
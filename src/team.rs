@@ -0,0 +1,116 @@
+//! Allowance of several travellers at once.
+
+use crate::{sort_trips, DateIntervalVec, Engine, Journal, Regime, Trip, Verdict};
+use anyhow::{Context, Result};
+use chrono::NaiveDate;
+use serde::Serialize;
+use std::io::Write;
+
+/// Allowance of one traveller on a date.
+#[derive(Debug, Clone, Serialize)]
+pub struct TravellerStatus {
+    pub name: String,
+    /// Days spent in the control period ending on the date.
+    pub days_used: usize,
+    pub days_remaining: usize,
+    /// Earliest date on or after the date on which entering the zone is allowed.
+    pub earliest_entry: NaiveDate,
+    /// Whether the days spent in the control period are within the allowance.
+    pub compliant: bool,
+}
+
+/// Order of travellers in the team report.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum SortKey {
+    Name,
+    DaysUsed,
+    DaysRemaining,
+    EarliestEntry,
+}
+
+impl Engine {
+    /// Calculates the allowance of a traveller on `date` from their trips.
+    pub fn status(&self, name: &str, trips: &[Trip], date: NaiveDate) -> Result<TravellerStatus> {
+        let stays = DateIntervalVec::from_trips(trips, date);
+        let check = self.check(&stays, date)?;
+        Ok(TravellerStatus {
+            name: name.to_string(),
+            days_used: check.days_used,
+            days_remaining: check.days_remaining,
+            earliest_entry: self.earliest_entry(&stays, date)?,
+            compliant: check.verdict == Verdict::Within,
+        })
+    }
+
+    /// Calculates the allowance of every traveller in the journal on `date`, counting the trips
+    /// that `regime` applies to without the days covered by the permits of the traveller.
+    pub fn team_status(
+        &self,
+        journal: &Journal,
+        regime: &Regime,
+        date: NaiveDate,
+    ) -> Result<Vec<TravellerStatus>> {
+        journal
+            .travellers
+            .iter()
+            .map(|traveller| {
+                let mut trips = traveller.trips()?;
+                sort_trips(&mut trips).with_context(|| format!("Traveller {}", traveller.name))?;
+                let (trips, _) = regime.counted_trips(&trips, &traveller.permits()?);
+                self.status(&traveller.name, &trips, date)
+            })
+            .collect()
+    }
+}
+
+/// Sorts the statuses by `key`, with ties broken by name.
+pub fn sort_statuses(statuses: &mut [TravellerStatus], key: SortKey) {
+    statuses.sort_by(|a, b| {
+        let ordering = match key {
+            SortKey::Name => std::cmp::Ordering::Equal,
+            SortKey::DaysUsed => b.days_used.cmp(&a.days_used),
+            SortKey::DaysRemaining => a.days_remaining.cmp(&b.days_remaining),
+            SortKey::EarliestEntry => b.earliest_entry.cmp(&a.earliest_entry),
+        };
+        ordering.then_with(|| a.name.cmp(&b.name))
+    });
+}
+
+/// Keeps the travellers with at most `max_remaining` days remaining, and only those exceeding the
+/// allowance if `non_compliant` is set.
+pub fn filter_statuses(
+    statuses: &mut Vec<TravellerStatus>,
+    max_remaining: Option<usize>,
+    non_compliant: bool,
+) {
+    statuses.retain(|s| {
+        max_remaining.is_none_or(|max| s.days_remaining <= max) && (!non_compliant || !s.compliant)
+    });
+}
+
+/// Writes the statuses as an aligned text table.
+pub fn write_table<W: Write>(mut w: W, statuses: &[TravellerStatus]) -> Result<()> {
+    let width = statuses
+        .iter()
+        .map(|s| s.name.chars().count())
+        .chain(Some(4))
+        .max()
+        .unwrap_or_default();
+    writeln!(
+        w,
+        "{:<width$}  {:>4}  {:>9}  {:<10}  {:<9}",
+        "name", "used", "remaining", "next entry", "compliant"
+    )?;
+    for s in statuses {
+        writeln!(
+            w,
+            "{:<width$}  {:>4}  {:>9}  {:<10}  {}",
+            s.name,
+            s.days_used,
+            s.days_remaining,
+            s.earliest_entry,
+            if s.compliant { "yes" } else { "no" }
+        )?;
+    }
+    Ok(())
+}
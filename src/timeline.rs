@@ -0,0 +1,75 @@
+//! Per-day allowance timeline.
+
+use crate::{DateInterval, DateIntervalVec, Engine};
+use anyhow::Result;
+use chrono::{Days, NaiveDate};
+use serde::Serialize;
+use std::io::Write;
+
+/// Allowance on a single day of the timeline.
+#[derive(Debug, Copy, Clone, Serialize)]
+pub struct TimelineDay {
+    pub date: NaiveDate,
+    /// Whether the day is spent in the zone.
+    pub in_zone: bool,
+    /// Days spent in the control period ending on this day.
+    pub days_used: usize,
+    /// Days still allowed in the control period ending on this day.
+    pub days_remaining: usize,
+}
+
+impl Engine {
+    /// Computes the allowance for every day in `range`.
+    pub fn timeline(
+        &self,
+        stays: &DateIntervalVec,
+        range: DateInterval,
+    ) -> Result<Vec<TimelineDay>> {
+        let mut days = Vec::with_capacity(range.abs_num_days());
+        let mut date = range.start();
+        while date <= range.end() {
+            let check = self.check(stays, date)?;
+            days.push(TimelineDay {
+                date,
+                in_zone: stays.contains(date),
+                days_used: check.days_used,
+                days_remaining: check.days_remaining,
+            });
+            date = date + Days::new(1);
+        }
+        Ok(days)
+    }
+}
+
+/// Writes the timeline as an aligned text table.
+pub fn write_table<W: Write>(mut w: W, days: &[TimelineDay]) -> Result<()> {
+    writeln!(
+        w,
+        "{:<10}  {:<7}  {:>4}  {:>9}",
+        "date", "in zone", "used", "remaining"
+    )?;
+    for day in days {
+        writeln!(
+            w,
+            "{:<10}  {:<7}  {:>4}  {:>9}",
+            day.date,
+            if day.in_zone { "yes" } else { "no" },
+            day.days_used,
+            day.days_remaining
+        )?;
+    }
+    Ok(())
+}
+
+/// Writes the timeline as CSV with a header row.
+pub fn write_csv<W: Write>(mut w: W, days: &[TimelineDay]) -> Result<()> {
+    writeln!(w, "date,in_zone,days_used,days_remaining")?;
+    for day in days {
+        writeln!(
+            w,
+            "{},{},{},{}",
+            day.date, day.in_zone, day.days_used, day.days_remaining
+        )?;
+    }
+    Ok(())
+}
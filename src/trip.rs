@@ -0,0 +1,274 @@
+//! Trips into the visa zone.
+
+use crate::DateInterval;
+use anyhow::Result;
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// Stay in the zone from the entry date to the exit date, both days included. An open trip has no
+/// exit date yet and lasts until the evaluation date.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Trip {
+    pub entry: NaiveDate,
+    pub exit: Option<NaiveDate>,
+    #[serde(flatten)]
+    pub info: TripInfo,
+    /// Countries visited on the trip in order. Empty if the trip is to a single country.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub legs: Vec<Leg>,
+    /// Line of the input the trip was read from, for diagnostics.
+    #[serde(skip)]
+    pub line: Option<usize>,
+}
+
+/// Optional description of a trip.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TripInfo {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub country: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub purpose: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub notes: Option<String>,
+    /// How the border of the zone was crossed on entry.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub border: Option<Border>,
+}
+
+/// Part of a trip spent in one country, from the day of arriving there until the day of arriving in
+/// the next country or the exit date of the trip. A day of travelling between two countries is
+/// spent in both.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Leg {
+    /// Day of arriving in the country.
+    pub date: NaiveDate,
+    pub country: String,
+    /// How the border of the country was crossed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub border: Option<Border>,
+}
+
+/// Way of crossing the border of the zone.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Border {
+    Land,
+    Sea,
+    Air,
+}
+
+impl fmt::Display for Border {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Land => write!(f, "land"),
+            Self::Sea => write!(f, "sea"),
+            Self::Air => write!(f, "air"),
+        }
+    }
+}
+
+impl FromStr for Border {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "land" => Ok(Self::Land),
+            "sea" => Ok(Self::Sea),
+            "air" => Ok(Self::Air),
+            _ => anyhow::bail!("Unknown border '{s}', expected land, sea or air"),
+        }
+    }
+}
+
+impl fmt::Display for TripInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let border = self.border.map(|border| format!("by {border}"));
+        let fields = [
+            &self.label,
+            &self.country,
+            &self.purpose,
+            &self.notes,
+            &border,
+        ];
+        let mut iter = fields
+            .iter()
+            .filter_map(|field| field.as_deref())
+            .peekable();
+        while let Some(field) = iter.next() {
+            write!(
+                f,
+                "{field}{}",
+                if iter.peek().is_some() { ", " } else { "" }
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl TripInfo {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+impl fmt::Display for Trip {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(exit) = self.exit {
+            write!(f, "trip from {} to {exit}", self.entry)?;
+        } else {
+            write!(f, "open trip from {}", self.entry)?;
+        }
+        if let Some(line) = self.line {
+            write!(f, " on line {line}")?;
+        }
+        Ok(())
+    }
+}
+
+impl Trip {
+    /// Makes a new trip, checking that the exit date is not before the entry date.
+    pub fn new(entry: NaiveDate, exit: NaiveDate) -> Result<Self> {
+        if exit < entry {
+            anyhow::bail!("Exit date {exit} is before the entry date {entry}");
+        }
+        Ok(Self {
+            entry,
+            exit: Some(exit),
+            info: TripInfo::default(),
+            legs: Vec::new(),
+            line: None,
+        })
+    }
+
+    /// Makes a new trip without an exit date.
+    pub fn open(entry: NaiveDate) -> Self {
+        Self {
+            entry,
+            exit: None,
+            info: TripInfo::default(),
+            legs: Vec::new(),
+            line: None,
+        }
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.exit.is_none()
+    }
+
+    pub fn at_line(mut self, line: usize) -> Self {
+        self.line = Some(line);
+        self
+    }
+
+    pub fn with_info(mut self, info: TripInfo) -> Self {
+        self.info = info;
+        self
+    }
+
+    /// Sets the legs of the trip, checking that they are in order and within the trip.
+    pub fn with_legs(mut self, legs: Vec<Leg>) -> Result<Self> {
+        for (a, b) in legs.iter().zip(legs.iter().skip(1)) {
+            if b.date < a.date {
+                anyhow::bail!(
+                    "Leg to {} on {} is before the leg to {}",
+                    b.country,
+                    b.date,
+                    a.country
+                );
+            }
+        }
+        for leg in &legs {
+            if leg.date < self.entry || self.exit.is_some_and(|exit| exit < leg.date) {
+                anyhow::bail!(
+                    "Leg to {} on {} is outside the {self}",
+                    leg.country,
+                    leg.date
+                );
+            }
+        }
+        self.legs = legs;
+        Ok(self)
+    }
+
+    /// Splits the trip into a trip to the country of each leg. Days before the first leg are spent
+    /// in the country of the trip. A trip without legs is returned as it is.
+    pub fn split_legs(&self) -> Vec<Trip> {
+        let Some(first) = self.legs.first() else {
+            return vec![self.clone()];
+        };
+        let part = |entry, exit, country, border| Trip {
+            entry,
+            exit,
+            info: TripInfo {
+                country,
+                border,
+                ..self.info.clone()
+            },
+            legs: Vec::new(),
+            line: self.line,
+        };
+        let mut parts = Vec::with_capacity(self.legs.len() + 1);
+        if self.entry < first.date {
+            parts.push(part(
+                self.entry,
+                Some(first.date),
+                self.info.country.clone(),
+                self.info.border,
+            ));
+        }
+        for (i, leg) in self.legs.iter().enumerate() {
+            let exit = self
+                .legs
+                .get(i + 1)
+                .map_or(self.exit, |next| Some(next.date));
+            parts.push(part(leg.date, exit, Some(leg.country.clone()), leg.border));
+        }
+        parts
+    }
+
+    /// Dates of the trip as an interval. An open trip lasts until `until`. Returns `None` if the
+    /// open trip starts after `until`.
+    pub fn interval(&self, until: NaiveDate) -> Option<DateInterval> {
+        DateInterval::new(self.entry, self.exit.unwrap_or(until)).ok()
+    }
+
+    /// Dates of the trip within `control_period`. An open trip lasts until the end of the control
+    /// period. Returns `None` if the trip is outside the control period.
+    pub fn clip(&self, control_period: DateInterval) -> Option<DateInterval> {
+        let mut di = self.interval(control_period.end())?;
+        if !di.overlaps(control_period) {
+            return None;
+        }
+        di.start_no_earlier(control_period.start());
+        di.end_no_later(control_period.end());
+        Some(di)
+    }
+}
+
+/// Sorts the trips by entry date and removes exact duplicates. Returns the number of removed
+/// duplicates.
+///
+/// Trips may share a day, when leaving the zone and entering it again on the same day, but they
+/// must not overlap otherwise. Only the last trip can be open.
+pub fn sort_trips(trips: &mut Vec<Trip>) -> Result<usize> {
+    trips.sort_by_key(|trip| (trip.entry, trip.exit));
+    let num_trips = trips.len();
+    trips.dedup_by(|b, a| a.entry == b.entry && a.exit == b.exit);
+    for (a, b) in trips.iter().zip(trips.iter().skip(1)) {
+        match a.exit {
+            None => anyhow::bail!("The {b} starts after the {a}"),
+            Some(exit) if b.entry < exit => anyhow::bail!("The {b} overlaps the {a}"),
+            _ => {}
+        }
+    }
+    Ok(num_trips - trips.len())
+}
+
+/// Returns the trip without an exit date, if any. Expects sorted trips.
+pub fn open_trip(trips: &[Trip]) -> Option<&Trip> {
+    trips.last().filter(|trip| trip.is_open())
+}
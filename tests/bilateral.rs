@@ -0,0 +1,92 @@
+//! Bilateral visa-waiver agreements.
+
+use chrono::NaiveDate;
+use multi_visa_calc::bilateral::{self, Agreement};
+use multi_visa_calc::{Engine, Journal, Trip, TripInfo};
+
+fn d(s: &str) -> NaiveDate {
+    NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+}
+
+fn trip(entry: &str, exit: &str, country: &str) -> Trip {
+    Trip::new(d(entry), d(exit)).unwrap().with_info(TripInfo {
+        country: Some(country.to_string()),
+        ..TripInfo::default()
+    })
+}
+
+#[test]
+fn days_over_in_the_country_of_the_agreement() {
+    let engine = Engine::default();
+    let agreements = bilateral::agreements();
+    let trips = [
+        trip("2024-01-01", "2024-02-29", "FR"),
+        trip("2024-03-01", "2024-04-15", "Poland"),
+    ];
+    let check = engine
+        .bilateral_check(&trips, d("2024-04-15"), "us", &agreements)
+        .unwrap()
+        .unwrap();
+    assert_eq!(check.days_over, 16);
+    assert_eq!(check.countries.len(), 1);
+    assert_eq!(check.countries[0].country.as_deref(), Some("PL"));
+    assert!(check.covered);
+
+    // Within the allowance, there is nothing to cover.
+    let check = engine
+        .bilateral_check(&trips, d("2024-03-29"), "US", &agreements)
+        .unwrap();
+    assert!(check.is_none());
+}
+
+#[test]
+fn days_over_elsewhere_are_not_covered() {
+    let trips = [
+        trip("2024-01-01", "2024-03-20", "PL"),
+        trip("2024-03-21", "2024-04-10", "DE"),
+    ];
+    let check = Engine::default()
+        .bilateral_check(&trips, d("2024-04-10"), "US", &bilateral::agreements())
+        .unwrap()
+        .unwrap();
+    assert_eq!(check.days_over, 11);
+    assert_eq!(check.countries[0].country.as_deref(), Some("DE"));
+    assert!(check.countries[0].agreement.is_none());
+    assert!(!check.covered);
+}
+
+#[test]
+fn agreements_from_options_and_journals() {
+    let agreement: Agreement = "nz:Germany:90".parse().unwrap();
+    assert_eq!(agreement.nationality, "NZ");
+    assert_eq!(agreement.country, "DE");
+    assert!("NZ:DE".parse::<Agreement>().is_err());
+
+    let journal = Journal::parse(
+        r#"
+[[agreement]]
+nationality = "NZ"
+country = "NL"
+days = 90
+
+[[agreement]]
+nationality = "nz"
+country = "Poland"
+days = 90
+"#,
+    )
+    .unwrap();
+    assert_eq!(journal.agreements[0].country, "NL");
+    // Countries given by name are stored by their code, as in the options.
+    assert_eq!(journal.agreements[1], "NZ:PL:90".parse().unwrap());
+    let trips = [
+        trip("2024-01-01", "2024-02-29", "FR"),
+        trip("2024-03-01", "2024-04-21", "PL"),
+    ];
+    let check = Engine::default()
+        .bilateral_check(&trips, d("2024-04-21"), "NZ", &journal.agreements)
+        .unwrap()
+        .unwrap();
+    assert_eq!(check.days_over, 22);
+    assert!(check.covered);
+}
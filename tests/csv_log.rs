@@ -0,0 +1,48 @@
+//! Import of CSV trip logs.
+
+use chrono::NaiveDate;
+use multi_visa_calc::{parse_csv_trips, ColumnMap};
+
+fn d(s: &str) -> NaiveDate {
+    NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+}
+
+#[test]
+fn header_is_detected_and_mapped() {
+    let input = "\
+Trip,Arrived,Departed,Country
+Berlin,2024-01-01,2024-01-10,DE
+Paris,2024-02-01,,FR
+";
+    let columns: ColumnMap = "entry=Arrived,exit=departed".parse().unwrap();
+    let trips = parse_csv_trips(input.as_bytes(), &columns).unwrap();
+    assert_eq!(trips.len(), 2);
+    assert_eq!(trips[0].exit, Some(d("2024-01-10")));
+    assert_eq!(trips[0].info.label.as_deref(), Some("Berlin"));
+    assert_eq!(trips[0].info.country.as_deref(), Some("DE"));
+    assert_eq!(trips[0].line, Some(2));
+    assert!(trips[1].is_open());
+}
+
+#[test]
+fn default_positions_without_header() {
+    let input = "2024-01-01,2024-01-10,DE,work,first\n";
+    let trips = parse_csv_trips(input.as_bytes(), &ColumnMap::default()).unwrap();
+    assert_eq!(trips[0].entry, d("2024-01-01"));
+    assert_eq!(trips[0].info.purpose.as_deref(), Some("work"));
+    assert_eq!(trips[0].info.notes.as_deref(), Some("first"));
+}
+
+#[test]
+fn errors_point_at_rows_and_columns() {
+    let input = "entry,exit\n2024-01-01,2024-01-10\n2024-02-01,2024-02-3x\n";
+    let err = parse_csv_trips(input.as_bytes(), &ColumnMap::default()).unwrap_err();
+    assert_eq!(
+        format!("{err:#}"),
+        "Row 3: column 2 (exit): Invalid date '2024-02-3x': trailing input"
+    );
+
+    let columns: ColumnMap = "entry=Start".parse().unwrap();
+    let err = parse_csv_trips(input.as_bytes(), &columns).unwrap_err();
+    assert_eq!(err.to_string(), "Column 'Start' not found in the header");
+}
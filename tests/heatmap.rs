@@ -0,0 +1,51 @@
+//! Calendar of the days spent in the zone.
+
+use chrono::NaiveDate;
+use multi_visa_calc::{DateIntervalVec, DayState, Engine, Heatmap, Trip};
+
+fn d(s: &str) -> NaiveDate {
+    NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+}
+
+fn heatmap(from: &str, num_months: u32) -> Heatmap {
+    Heatmap {
+        from: d(from),
+        num_months,
+        columns: 3,
+        color: false,
+    }
+}
+
+#[test]
+fn day_states() {
+    let trips = [
+        Trip::new(d("2024-01-01"), d("2024-01-10")).unwrap(),
+        Trip::new(d("2024-07-01"), d("2024-07-05")).unwrap(),
+    ];
+    let stays = DateIntervalVec::from_trips(&trips, d("2024-07-05"));
+    let control_period = Engine::default().control_period(d("2024-07-05")).unwrap();
+    let days = heatmap("2024-01-15", 7).days(&stays, control_period);
+
+    assert_eq!(days.first().unwrap().date, d("2024-01-01"));
+    assert_eq!(days.last().unwrap().date, d("2024-07-31"));
+    let state = |date| days.iter().find(|day| day.date == d(date)).unwrap().state;
+    // The control period starts on 2024-01-08.
+    assert_eq!(state("2024-01-07"), DayState::InZone);
+    assert_eq!(state("2024-01-08"), DayState::Counted);
+    assert_eq!(state("2024-01-11"), DayState::Outside);
+    assert_eq!(state("2024-07-05"), DayState::Counted);
+}
+
+#[test]
+fn render_without_colour() {
+    let trips = [Trip::new(d("2024-02-05"), d("2024-02-07")).unwrap()];
+    let stays = DateIntervalVec::from_trips(&trips, d("2024-02-29"));
+    let control_period = Engine::default().control_period(d("2024-02-29")).unwrap();
+    let text = heatmap("2024-02-01", 1).render(&stays, control_period);
+    let lines: Vec<_> = text.lines().collect();
+
+    assert_eq!(lines[0].trim(), "February 2024");
+    assert_eq!(lines[3], " 5#  6#  7#  8   9  10  11");
+    assert_eq!(lines[6], "26  27  28  29<");
+    assert!(!text.contains('\x1b'));
+}
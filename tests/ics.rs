@@ -0,0 +1,104 @@
+//! Import of trips from calendars.
+
+use chrono::NaiveDate;
+use multi_visa_calc::{parse_ics_trips, EventFilter};
+
+fn d(s: &str) -> NaiveDate {
+    NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+}
+
+const CALENDAR: &str = "\
+BEGIN:VCALENDAR\r
+BEGIN:VEVENT\r
+DTSTART;VALUE=DATE:20240301\r
+DTEND;VALUE=DATE:20240303\r
+SUMMARY:Trip to\r
+  Paris\r
+CATEGORIES:Travel,Work\r
+END:VEVENT\r
+BEGIN:VEVENT\r
+DTSTART;VALUE=DATE:20240301\r
+DTEND;VALUE=DATE:20240310\r
+SUMMARY:Lyon\r
+CATEGORIES:TRAVEL\r
+END:VEVENT\r
+BEGIN:VEVENT\r
+DTSTART;VALUE=DATE:20240401\r
+SUMMARY:Birthday\r
+END:VEVENT\r
+END:VCALENDAR\r
+";
+
+#[test]
+fn all_day_end_dates_are_exclusive() {
+    let filter = EventFilter {
+        keyword: Some("paris".to_string()),
+        ..EventFilter::default()
+    };
+    let import = parse_ics_trips(CALENDAR.as_bytes(), &filter).unwrap();
+    assert_eq!(import.trips.len(), 1);
+    assert_eq!(import.trips[0].entry, d("2024-03-01"));
+    assert_eq!(import.trips[0].exit, Some(d("2024-03-02")));
+    assert_eq!(import.trips[0].info.label.as_deref(), Some("Trip to Paris"));
+    assert!(import.warnings.is_empty());
+}
+
+#[test]
+fn overlapping_events_are_merged() {
+    let filter = EventFilter {
+        category: Some("travel".to_string()),
+        ..EventFilter::default()
+    };
+    let import = parse_ics_trips(CALENDAR.as_bytes(), &filter).unwrap();
+    assert_eq!(import.trips.len(), 1);
+    assert_eq!(import.trips[0].exit, Some(d("2024-03-09")));
+    assert_eq!(import.warnings.len(), 1);
+
+    let import = parse_ics_trips(CALENDAR.as_bytes(), &EventFilter::default()).unwrap();
+    assert_eq!(import.trips.len(), 2);
+    assert_eq!(import.trips[1].entry, d("2024-04-01"));
+    assert_eq!(import.trips[1].exit, Some(d("2024-04-01")));
+}
+
+#[test]
+fn durations_and_alarms() {
+    let calendar = "\
+BEGIN:VCALENDAR\r
+BEGIN:VEVENT\r
+DTSTART;VALUE=DATE:20240501\r
+DURATION:P5D\r
+DESCRIPTION:Schengen trip\r
+BEGIN:VALARM\r
+ACTION:DISPLAY\r
+DESCRIPTION:Reminder\r
+TRIGGER:-P1D\r
+END:VALARM\r
+SUMMARY:Rome\r
+END:VEVENT\r
+BEGIN:VEVENT\r
+DTSTART:20240610T180000\r
+DURATION:PT36H\r
+DESCRIPTION:Schengen trip\r
+END:VEVENT\r
+END:VCALENDAR\r
+";
+    let filter = EventFilter {
+        keyword: Some("schengen".to_string()),
+        ..EventFilter::default()
+    };
+    let import = parse_ics_trips(calendar.as_bytes(), &filter).unwrap();
+    assert_eq!(import.trips.len(), 2);
+    assert_eq!(import.trips[0].entry, d("2024-05-01"));
+    assert_eq!(import.trips[0].exit, Some(d("2024-05-05")));
+    assert_eq!(import.trips[0].info.label.as_deref(), Some("Rome"));
+    assert_eq!(import.trips[1].entry, d("2024-06-10"));
+    assert_eq!(import.trips[1].exit, Some(d("2024-06-12")));
+    assert!(import.warnings.is_empty());
+
+    let filter = EventFilter {
+        keyword: Some("reminder".to_string()),
+        ..EventFilter::default()
+    };
+    let import = parse_ics_trips(calendar.as_bytes(), &filter).unwrap();
+    assert!(import.trips.is_empty());
+}
@@ -0,0 +1,56 @@
+//! Trip journals with several travellers.
+
+use chrono::NaiveDate;
+use multi_visa_calc::Journal;
+
+fn d(s: &str) -> NaiveDate {
+    NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+}
+
+const JOURNAL: &str = r#"
+[[traveller]]
+name = "Alice"
+nationality = "GB"
+regimes = ["schengen"]
+
+[[traveller.trip]]
+entry = 2024-01-05
+exit = 2024-01-20
+country = "FR"
+
+[[traveller.trip]]
+entry = "2024-03-01"
+
+[[traveller]]
+name = "Bob"
+"#;
+
+#[test]
+fn travellers_are_selected_by_name() {
+    let journal = Journal::parse(JOURNAL).unwrap();
+    let alice = journal.traveller(Some("alice")).unwrap();
+    assert_eq!(alice.nationality.as_deref(), Some("GB"));
+    assert_eq!(alice.regimes, ["schengen"]);
+
+    let trips = alice.trips().unwrap();
+    assert_eq!(trips[0].exit, Some(d("2024-01-20")));
+    assert_eq!(trips[0].info.country.as_deref(), Some("FR"));
+    assert_eq!(trips[1].entry, d("2024-03-01"));
+    assert!(trips[1].is_open());
+
+    assert!(journal.traveller(Some("Carol")).is_err());
+    assert_eq!(
+        journal.traveller(None).unwrap_err().to_string(),
+        "Choose a traveller: Alice, Bob"
+    );
+}
+
+#[test]
+fn reversed_trips_are_rejected() {
+    let journal = Journal::parse(
+        "[[traveller]]\nname = \"A\"\n[[traveller.trip]]\nentry = 2024-02-01\nexit = 2024-01-01\n",
+    )
+    .unwrap();
+    let err = journal.traveller(None).unwrap().trips().unwrap_err();
+    assert_eq!(err.to_string(), "Traveller A, trip 1");
+}
@@ -0,0 +1,144 @@
+//! Versioned JSON output of the commands.
+
+use serde_json::Value;
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+/// Runs the calculator with `trips` on the standard input and parses its JSON output.
+fn run(trips: &str, args: &[&str]) -> (Option<i32>, Value) {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_multi-visa-calc"))
+        .args(["--format", "json"])
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(trips.as_bytes())
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+    (
+        output.status.code(),
+        serde_json::from_slice(&output.stdout).unwrap(),
+    )
+}
+
+fn keys(value: &Value) -> Vec<&str> {
+    let mut keys: Vec<_> = value
+        .as_object()
+        .unwrap()
+        .keys()
+        .map(String::as_str)
+        .collect();
+    keys.sort();
+    keys
+}
+
+#[test]
+fn check_result_schema() {
+    let trips = "2024-01-01 2024-01-10\n2024-01-01 2024-01-10\n2024-03-01 2024-03-05\n";
+    let (code, report) = run(trips, &["check", "--end", "2024-03-31"]);
+    assert_eq!(code, Some(0));
+    assert_eq!(
+        keys(&report),
+        ["command", "result", "schema_version", "warnings"]
+    );
+    assert_eq!(report["schema_version"], 1);
+    assert_eq!(report["command"], "check");
+    assert_eq!(report["warnings"][0], "1 duplicate trip found and removed");
+
+    let result = &report["result"];
+    assert_eq!(
+        keys(result),
+        [
+            "control_period",
+            "days_over",
+            "days_remaining",
+            "days_used",
+            "excluded",
+            "intervals",
+            "open_trip",
+            "rule",
+            "trips",
+            "verdict",
+        ]
+    );
+    assert_eq!(keys(&result["control_period"]), ["days", "end", "start"]);
+    assert_eq!(result["control_period"]["end"], "2024-03-31");
+    assert_eq!(result["control_period"]["days"], 180);
+    assert_eq!(result["intervals"][1]["start"], "2024-03-01");
+    assert_eq!(result["intervals"][1]["days"], 5);
+    assert_eq!(result["days_used"], 15);
+    assert_eq!(result["days_remaining"], 75);
+    assert_eq!(result["days_over"], 0);
+    assert_eq!(result["verdict"], "within");
+    assert_eq!(keys(&result["rule"]), ["allowed", "period"]);
+    assert_eq!(keys(&result["trips"][0]), ["counted", "entry", "exit"]);
+}
+
+#[test]
+fn exceeding_the_allowance() {
+    let (code, report) = run("2024-01-01 2024-04-30\n", &["check", "--end", "2024-04-30"]);
+    assert_eq!(code, Some(1));
+    assert_eq!(report["result"]["verdict"], "exceeds");
+    assert_eq!(report["result"]["days_over"], 31);
+    assert_eq!(report["warnings"].as_array().unwrap().len(), 0);
+
+    let (code, report) = run(
+        "2024-01-01 2024-04-30\n",
+        &["next-entry", "--from", "2024-05-01"],
+    );
+    assert_eq!(code, Some(0));
+    assert_eq!(report["command"], "next-entry");
+    assert_eq!(keys(&report["result"]), ["earliest_entry", "from"]);
+}
+
+#[test]
+fn trips_left_out_of_the_residence_test() {
+    let trips = "entry,exit,country\n\
+                 2024-04-10,2024-12-20,UK\n\
+                 2025-01-05,2025-01-20,\n\
+                 2025-02-01,2025-02-10,Atlantis\n";
+    let (code, report) = run(trips, &["--input-format", "csv", "srt", "--year", "2024"]);
+    assert_eq!(code, Some(0));
+    assert_eq!(report["result"]["uk_days"], 254);
+    assert_eq!(report["result"]["resident"], true);
+    assert_eq!(
+        report["warnings"],
+        serde_json::json!([
+            "1 trip without a country not counted",
+            "Trips to unknown countries not counted: Atlantis",
+        ])
+    );
+}
+
+#[test]
+fn threshold_is_reached_with_planned_trips_to_the_country() {
+    let planned = std::path::Path::new(env!("CARGO_TARGET_TMPDIR")).join("planned-es.txt");
+    std::fs::write(&planned, "2024-08-01 2024-08-31\n").unwrap();
+    let (code, report) = run(
+        "entry,exit,country\n2024-01-01,2024-06-30,ES\n",
+        &[
+            "--input-format",
+            "csv",
+            "tax-days",
+            "--country",
+            "ES",
+            "--planned",
+            planned.to_str().unwrap(),
+            "--from-year",
+            "2024",
+            "--to-year",
+            "2024",
+        ],
+    );
+    assert_eq!(code, Some(0));
+    let year = &report["result"]["years"][0];
+    assert_eq!(year["days"], 182);
+    assert_eq!(year["planned_days"], 182 + 31);
+    assert_eq!(year["threshold_reached"], "2024-08-01");
+}
@@ -0,0 +1,123 @@
+//! Trips through several countries and regimes.
+
+use chrono::NaiveDate;
+use multi_visa_calc::{regime, Journal, Leg, Regime, Trip};
+
+fn d(s: &str) -> NaiveDate {
+    NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+}
+
+fn leg(date: &str, country: &str) -> Leg {
+    Leg {
+        date: d(date),
+        country: country.to_string(),
+        border: None,
+    }
+}
+
+#[test]
+fn transit_days_count_in_both_regimes() {
+    let trip = Trip::new(d("2024-03-01"), d("2024-03-12"))
+        .unwrap()
+        .with_legs(vec![
+            leg("2024-03-01", "DE"),
+            leg("2024-03-05", "GB"),
+            leg("2024-03-09", "FR"),
+        ])
+        .unwrap();
+    let parts = trip.split_legs();
+    assert_eq!(parts.len(), 3);
+    assert_eq!(parts[1].entry, d("2024-03-05"));
+    assert_eq!(parts[1].exit, Some(d("2024-03-09")));
+
+    let trips = [trip];
+    let schengen = Regime::default()
+        .usage(&trips, &[], d("2024-03-31"))
+        .unwrap();
+    assert_eq!(schengen.check.days_used, 5 + 4);
+    let uk: Regime = "uk-visitor".parse().unwrap();
+    assert_eq!(
+        uk.usage(&trips, &[], d("2024-03-31"))
+            .unwrap()
+            .check
+            .days_used,
+        5
+    );
+}
+
+#[test]
+fn days_before_the_first_leg_are_in_the_country_of_the_trip() {
+    let mut trip = Trip::open(d("2024-05-01"))
+        .with_legs(vec![leg("2024-05-04", "TR")])
+        .unwrap();
+    trip.info.country = Some("GR".to_string());
+    let parts = trip.split_legs();
+    assert_eq!(parts[0].info.country.as_deref(), Some("GR"));
+    assert_eq!(parts[0].exit, Some(d("2024-05-04")));
+    assert!(parts[1].is_open());
+
+    let err = Trip::new(d("2024-05-01"), d("2024-05-10"))
+        .unwrap()
+        .with_legs(vec![leg("2024-05-11", "TR")])
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Leg to TR on 2024-05-11 is outside the trip from 2024-05-01 to 2024-05-10"
+    );
+}
+
+#[test]
+fn journal_legs() {
+    let journal = Journal::parse(
+        r#"
+[[traveller]]
+name = "Bob"
+
+[[traveller.trip]]
+entry = 2024-03-01
+exit = 2024-03-12
+
+[[traveller.trip.leg]]
+date = 2024-03-01
+country = "DE"
+
+[[traveller.trip.leg]]
+date = "2024-03-05"
+country = "GB"
+border = "air"
+"#,
+    )
+    .unwrap();
+    let trips = journal.traveller(None).unwrap().trips().unwrap();
+    assert_eq!(trips[0].legs.len(), 2);
+    assert_eq!(trips[0].legs[1].country, "GB");
+}
+
+#[test]
+fn trips_without_a_country_count_under_the_selected_regime() {
+    let mut france = Trip::new(d("2024-04-01"), d("2024-04-05")).unwrap();
+    france.info.country = Some("FR".to_string());
+    let mut turkey = Trip::new(d("2024-05-01"), d("2024-05-03")).unwrap();
+    turkey.info.country = Some("TR".to_string());
+    let trips = [
+        Trip::new(d("2024-03-01"), d("2024-03-10")).unwrap(),
+        france,
+        turkey,
+    ];
+    assert_eq!(regime::num_unlabelled(&trips), 1);
+
+    let days_used = |regimes: &[Regime], selected: &str| -> Vec<usize> {
+        let selected: Regime = selected.parse().unwrap();
+        regime::usages(regimes, &selected, &trips, &[], d("2024-05-31"))
+            .unwrap()
+            .iter()
+            .map(|usage| usage.check.days_used)
+            .collect()
+    };
+    let all = Regime::builtin();
+    assert_eq!(days_used(&all, "schengen"), [15, 3, 0]);
+    assert_eq!(days_used(&all, "uk-visitor"), [5, 3, 10]);
+    assert_eq!(days_used(&all[..2], "uk-visitor"), [5, 3]);
+    // A single regime counts them whichever regime is selected.
+    assert_eq!(days_used(&all[1..2], "schengen"), [13]);
+}
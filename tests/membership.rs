@@ -0,0 +1,87 @@
+//! Countries of the zone and the dates they joined it.
+
+use chrono::NaiveDate;
+use multi_visa_calc::{
+    parse_csv_trips, Border, ColumnMap, DateIntervalVec, Regime, Trip, TripInfo,
+};
+
+fn d(s: &str) -> NaiveDate {
+    NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+}
+
+fn trip(entry: &str, exit: &str, country: &str, border: Option<Border>) -> Trip {
+    Trip::new(d(entry), d(exit)).unwrap().with_info(TripInfo {
+        country: Some(country.to_string()),
+        border,
+        ..TripInfo::default()
+    })
+}
+
+#[test]
+fn days_count_from_the_accession_date() {
+    let regime = Regime::default();
+    let trips = [
+        trip("2022-12-20", "2023-01-05", "Croatia", None),
+        trip("2023-02-01", "2023-02-10", "HR", Some(Border::Air)),
+        trip("2024-03-20", "2024-04-05", "BG", Some(Border::Air)),
+        trip("2024-06-01", "2024-06-10", "RO", Some(Border::Land)),
+        trip("2024-12-28", "2025-01-03", "ro", Some(Border::Land)),
+    ];
+    let trips = regime.applicable_trips(&trips);
+    let entries: Vec<_> = trips.iter().map(|trip| trip.entry).collect();
+    assert_eq!(
+        entries,
+        [
+            d("2023-01-01"),
+            d("2023-02-01"),
+            d("2024-03-31"),
+            d("2025-01-01")
+        ]
+    );
+    let stays = DateIntervalVec::from_trips(&trips, d("2025-01-03"));
+    assert_eq!(stays.num_spent_days(), 5 + 10 + 6 + 3);
+}
+
+#[test]
+fn trips_outside_the_zone_are_not_counted() {
+    let regime = Regime::default();
+    let trips = [
+        trip("2024-01-01", "2024-01-10", "FR", None),
+        trip("2024-01-10", "2024-01-20", "GB", None),
+        Trip::new(d("2024-02-01"), d("2024-02-05")).unwrap(),
+    ];
+    let trips = regime.applicable_trips(&trips);
+    assert_eq!(trips.len(), 2);
+    assert_eq!(trips[1].info.country, None);
+
+    // Regimes without a list of members count every trip.
+    let custom = Regime {
+        name: "custom".to_string(),
+        members: Vec::new(),
+        ..Regime::default()
+    };
+    assert_eq!(
+        custom
+            .applicable_trips(&[trip("2024-01-10", "2024-01-20", "GB", None)])
+            .len(),
+        1
+    );
+}
+
+#[test]
+fn border_column() {
+    let input =
+        "entry,exit,country,border\n2024-04-01,2024-04-10,BG,Land\n2024-05-01,2024-05-10,BG,\n";
+    let trips = parse_csv_trips(input.as_bytes(), &ColumnMap::default()).unwrap();
+    assert_eq!(trips[0].info.border, Some(Border::Land));
+    assert_eq!(trips[1].info.border, None);
+    // Land borders of Bulgaria opened on 2025-01-01.
+    assert!(Regime::default().applicable_trips(&trips[..1]).is_empty());
+
+    let input = "entry,exit,border\n2024-04-01,2024-04-10,rail\n";
+    let err = parse_csv_trips(input.as_bytes(), &ColumnMap::default()).unwrap_err();
+    assert_eq!(
+        format!("{err:#}"),
+        "Row 2: column 3 (border): Unknown border 'rail', expected land, sea or air"
+    );
+}
@@ -0,0 +1,93 @@
+//! Deadlines and allowance milestones, and their calendar export.
+
+use chrono::NaiveDate;
+use multi_visa_calc::{ics, DateIntervalVec, Engine, Milestone, MilestoneKind, Trip};
+
+fn d(s: &str) -> NaiveDate {
+    NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+}
+
+#[test]
+fn recovery_dates() {
+    let trips = [Trip::new(d("2024-01-01"), d("2024-03-30")).unwrap()];
+    let stays = DateIntervalVec::from_trips(&trips, d("2024-04-01"));
+    let engine = Engine::default();
+    let recovery = |days| engine.recovery_date(&stays, d("2024-04-01"), days).unwrap();
+    assert_eq!(recovery(0), Some(d("2024-04-01")));
+    assert_eq!(recovery(1), Some(d("2024-06-29")));
+    assert_eq!(recovery(30), Some(d("2024-07-28")));
+    // The whole allowance is back once the stays are out of the control period.
+    assert_eq!(recovery(90), Some(d("2024-09-26")));
+    assert_eq!(recovery(91), None);
+}
+
+#[test]
+fn open_trip_lasts_until_the_last_day_to_leave() {
+    let trips = [
+        Trip::new(d("2024-01-01"), d("2024-01-30")).unwrap(),
+        Trip::open(d("2024-03-01")),
+    ];
+    let milestones = Engine::default()
+        .milestones(&trips, d("2024-03-10"), &[30, 90])
+        .unwrap();
+    let dates: Vec<_> = milestones.iter().map(|m| (m.kind, m.date)).collect();
+    assert_eq!(
+        dates,
+        [
+            (MilestoneKind::LeaveBy, d("2024-04-29")),
+            (MilestoneKind::EarliestEntry, d("2024-06-29")),
+            (MilestoneKind::Recovery { days: 30 }, d("2024-07-28")),
+            (MilestoneKind::Recovery { days: 90 }, d("2024-10-26")),
+        ]
+    );
+
+    // Without an open trip the milestones are counted from the date.
+    let milestones = Engine::default()
+        .milestones(&trips[..1], d("2024-03-10"), &[90])
+        .unwrap();
+    let dates: Vec<_> = milestones.iter().map(|m| (m.kind, m.date)).collect();
+    assert_eq!(
+        dates,
+        [
+            (MilestoneKind::EarliestEntry, d("2024-03-10")),
+            (MilestoneKind::Recovery { days: 90 }, d("2024-07-28")),
+        ]
+    );
+}
+
+#[test]
+fn calendar_lines_are_folded() {
+    let milestones = [Milestone {
+        date: d("2024-06-29"),
+        kind: MilestoneKind::EarliestEntry,
+        summary: "Earliest entry into the zone, after the stays in Spain; Italy & Österreich \
+                  have left the control period"
+            .to_string(),
+    }];
+    let stamp = d("2024-03-10").and_hms_opt(12, 0, 0).unwrap();
+    let mut out = Vec::new();
+    ics::write_milestones(&mut out, &milestones, stamp).unwrap();
+    let out = String::from_utf8(out).unwrap();
+
+    assert!(out.ends_with("END:VCALENDAR\r\n"));
+    let lines: Vec<_> = out.split_terminator("\r\n").collect();
+    assert!(lines.iter().all(|line| line.len() <= 75));
+    assert!(lines.contains(&"UID:20240629-earliest-entry@multi-visa-calc"));
+    assert!(lines.contains(&"DTSTAMP:20240310T120000Z"));
+    assert!(lines.contains(&"DTSTART;VALUE=DATE:20240629"));
+    assert!(lines.contains(&"DTEND;VALUE=DATE:20240630"));
+
+    let unfolded = out.replace("\r\n ", "");
+    assert!(unfolded.contains(
+        "SUMMARY:Earliest entry into the zone\\, after the stays in Spain\\; Italy & Österreich \
+         have left the control period\r\n"
+    ));
+    let summary = lines
+        .iter()
+        .position(|l| l.starts_with("SUMMARY:"))
+        .unwrap();
+    // Lines are folded between characters, so the `Ö` starting at the 75th octet moves to the next
+    // line.
+    assert_eq!(lines[summary].len(), 74);
+    assert!(lines[summary + 1].starts_with(" Österreich"));
+}
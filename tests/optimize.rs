@@ -0,0 +1,74 @@
+//! Schedules that maximise the days in the zone.
+
+use chrono::NaiveDate;
+use multi_visa_calc::{Constraints, DateInterval, DateIntervalVec, Engine};
+
+fn d(s: &str) -> NaiveDate {
+    NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+}
+
+fn di(a: &str, b: &str) -> DateInterval {
+    DateInterval::new(d(a), d(b)).unwrap()
+}
+
+#[test]
+fn one_day_trips_use_the_whole_allowance() {
+    let constraints = Constraints {
+        horizon: di("2024-01-01", "2024-07-15"),
+        outside: vec![di("2024-03-01", "2024-03-01")],
+        min_trip_days: 1,
+    };
+    let schedule = Engine::default()
+        .optimize(&DateIntervalVec::default(), &constraints)
+        .unwrap();
+    assert_eq!(
+        schedule,
+        [
+            di("2024-01-01", "2024-02-29"),
+            di("2024-03-02", "2024-03-31"),
+            di("2024-06-29", "2024-07-15"),
+        ]
+    );
+}
+
+#[test]
+fn long_minimum_trip_waits_for_a_longer_stay() {
+    // Entering on the first day only leaves 60 days before the day outside, and no other trip of
+    // 60 days fits in the horizon after it.
+    let constraints = Constraints {
+        horizon: di("2024-01-01", "2024-07-15"),
+        outside: vec![di("2024-03-01", "2024-03-01")],
+        min_trip_days: 60,
+    };
+    let schedule = Engine::default()
+        .optimize(&DateIntervalVec::default(), &constraints)
+        .unwrap();
+    assert_eq!(schedule, [di("2024-03-02", "2024-05-30")]);
+}
+
+#[test]
+fn history_and_minimum_length_are_respected() {
+    // 8 of 10 days used in the 20 days before the horizon.
+    let history = DateIntervalVec::from(vec![di("2024-01-05", "2024-01-12")]);
+    let engine = Engine::new(20, 10);
+    let constraints = Constraints {
+        horizon: di("2024-01-15", "2024-02-15"),
+        outside: vec![di("2024-01-28", "2024-01-28")],
+        min_trip_days: 4,
+    };
+    let schedule = engine.optimize(&history, &constraints).unwrap();
+    let total: usize = schedule.iter().map(DateInterval::abs_num_days).sum();
+    assert_eq!(total, 14);
+    assert!(schedule.iter().all(|trip| trip.abs_num_days() >= 4));
+    assert!(schedule.iter().all(|trip| !trip.contains(d("2024-01-28"))));
+
+    // Every day of the schedule is allowed.
+    let stays = schedule
+        .iter()
+        .fold(history, |stays, &trip| stays.with(trip));
+    for trip in &schedule {
+        for date in trip.start().iter_days().take(trip.abs_num_days()) {
+            assert_eq!(engine.check(&stays, date).unwrap().days_over, 0);
+        }
+    }
+}
@@ -0,0 +1,89 @@
+//! Long-stay visas and residence permits.
+
+use chrono::NaiveDate;
+use multi_visa_calc::permit::exclude_permits;
+use multi_visa_calc::{DateInterval, Permit, PermitKind, Regime, Trip, TripInfo};
+
+fn d(s: &str) -> NaiveDate {
+    NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+}
+
+fn permit(from: &str, to: &str, country: Option<&str>) -> Permit {
+    Permit {
+        kind: PermitKind::ResidencePermit,
+        country: country.map(str::to_string),
+        period: DateInterval::new(d(from), d(to)).unwrap(),
+    }
+}
+
+#[test]
+fn covered_days_are_cut_out_of_trips() {
+    let trips = [
+        Trip::new(d("2024-01-01"), d("2024-03-31")).unwrap(),
+        Trip::open(d("2024-06-01")),
+    ];
+    let permits = [
+        permit("2024-02-01", "2024-02-29", None),
+        permit("2024-05-01", "2024-06-30", None),
+    ];
+    let (trips, excluded) = exclude_permits(&trips, &permits);
+
+    let dates: Vec<_> = trips.iter().map(|trip| (trip.entry, trip.exit)).collect();
+    assert_eq!(
+        dates,
+        [
+            (d("2024-01-01"), Some(d("2024-01-31"))),
+            (d("2024-03-01"), Some(d("2024-03-31"))),
+            (d("2024-07-01"), None),
+        ]
+    );
+    assert_eq!(excluded.len(), 2);
+    assert_eq!(excluded[0].days.abs_num_days(), 29);
+    assert_eq!(excluded[1].days.start(), d("2024-06-01"));
+    assert_eq!(excluded[1].days.end(), d("2024-06-30"));
+}
+
+#[test]
+fn permits_apply_to_the_regime_of_their_country() {
+    let info = TripInfo {
+        country: Some("GB".to_string()),
+        ..TripInfo::default()
+    };
+    let trips = [Trip::new(d("2024-01-01"), d("2024-01-10"))
+        .unwrap()
+        .with_info(info)];
+    let permits = [permit("2024-01-01", "2024-12-31", Some("DE"))];
+
+    let uk: Regime = "uk-visitor".parse().unwrap();
+    let (counted, excluded) = uk.counted_trips(&trips, &permits);
+    assert_eq!(counted.len(), 1);
+    assert!(excluded.is_empty());
+
+    let usage = uk
+        .usage(
+            &trips,
+            &[permit("2024-01-05", "2024-12-31", Some("GB"))],
+            d("2024-01-10"),
+        )
+        .unwrap();
+    assert_eq!(usage.check.days_used, 4);
+    assert_eq!(usage.excluded[0].days.abs_num_days(), 6);
+}
+
+#[test]
+fn parse_permit() {
+    let permit: Permit = "d-visa:2024-01-01..2024-06-30".parse().unwrap();
+    assert_eq!(permit.kind, PermitKind::LongStayVisa);
+    assert_eq!(permit.period.abs_num_days(), 182);
+    assert_eq!(
+        permit.to_string(),
+        "long-stay visa from 2024-01-01 to 2024-06-30"
+    );
+    let err = "visitor:2024-01-01..2024-06-30"
+        .parse::<Permit>()
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Unknown permit 'visitor', expected long-stay-visa, d-visa or residence-permit"
+    );
+}
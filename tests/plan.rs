@@ -0,0 +1,83 @@
+//! Day-by-day check of planned trips.
+
+use chrono::NaiveDate;
+use multi_visa_calc::{DateIntervalVec, Engine, Trip};
+
+fn d(s: &str) -> NaiveDate {
+    NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+}
+
+fn trip(entry: &str, exit: &str) -> Trip {
+    Trip::new(d(entry), d(exit)).unwrap()
+}
+
+#[test]
+fn planned_trips_add_up() {
+    // 60 days in the history.
+    let history = DateIntervalVec::from_trips(&[trip("2024-01-01", "2024-02-29")], d("2024-03-01"));
+    let engine = Engine::default();
+
+    // 30 more days fit on their own.
+    let alone = engine
+        .check_planned(&history, &[trip("2024-04-01", "2024-04-30")])
+        .unwrap();
+    assert!(alone[0].breach.is_none());
+
+    // After 16 planned days, only 14 of them do.
+    let planned = [
+        trip("2024-03-10", "2024-03-25"),
+        trip("2024-04-01", "2024-04-30"),
+    ];
+    let checked = engine.check_planned(&history, &planned).unwrap();
+    assert!(checked[0].breach.is_none());
+    let breach = checked[1].breach.unwrap();
+    assert_eq!(breach.first_day, d("2024-04-15"));
+    // 60 + 16 + 30 days on the last day of the trip.
+    assert_eq!(breach.days_over, 16);
+    assert_eq!(breach.latest_exit, Some(d("2024-04-14")));
+}
+
+#[test]
+fn entering_is_not_allowed() {
+    let history = DateIntervalVec::from_trips(&[trip("2024-01-01", "2024-03-30")], d("2024-04-01"));
+    let checked = Engine::default()
+        .check_planned(&history, &[trip("2024-04-10", "2024-04-12")])
+        .unwrap();
+    let breach = checked[0].breach.unwrap();
+    assert_eq!(breach.first_day, d("2024-04-10"));
+    assert_eq!(breach.days_over, 3);
+    assert_eq!(breach.latest_exit, None);
+
+    let err = Engine::default()
+        .check_planned(&history, &[Trip::open(d("2024-04-10"))])
+        .unwrap_err();
+    assert!(err.to_string().contains("has no exit date"));
+}
+
+#[test]
+fn open_trip_lasts_until_the_last_day_to_leave() {
+    // 59 days used before the current stay.
+    let trips = [
+        trip("2026-06-01", "2026-07-29"),
+        Trip::open(d("2026-10-01")),
+    ];
+    let engine = Engine::default();
+    let planned = [trip("2026-11-20", "2026-12-20")];
+    let until = engine
+        .open_trip_until(&trips, &planned, d("2026-10-16"))
+        .unwrap();
+    assert_eq!(until, d("2026-10-31"));
+    let history = DateIntervalVec::from_trips(&trips, until);
+    let checked = engine.check_planned(&history, &planned).unwrap();
+    assert_eq!(checked[0].breach.unwrap().first_day, d("2026-11-20"));
+
+    // The stay ends before an earlier planned trip.
+    let planned = [trip("2026-10-25", "2026-10-26")];
+    let until = engine.open_trip_until(&trips, &planned, d("2026-10-16"));
+    assert_eq!(until.unwrap(), d("2026-10-24"));
+    // It cannot end before today.
+    let until = engine.open_trip_until(&trips, &planned, d("2026-11-05"));
+    assert_eq!(until.unwrap(), d("2026-11-05"));
+    let until = engine.open_trip_until(&trips[..1], &planned, d("2026-10-16"));
+    assert_eq!(until.unwrap(), d("2026-10-16"));
+}
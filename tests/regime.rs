@@ -0,0 +1,87 @@
+//! Built-in visa regimes.
+
+use chrono::NaiveDate;
+use multi_visa_calc::{Convention, DateIntervalVec, Regime, Trip, Verdict};
+
+fn d(s: &str) -> NaiveDate {
+    NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+}
+
+#[test]
+fn single_stay_limit() {
+    let regime: Regime = "uk-visitor".parse().unwrap();
+    let engine = regime.engine();
+    let trips = [Trip::new(d("2024-01-01"), d("2024-06-29")).unwrap()];
+    let stays = DateIntervalVec::from_trips(&trips, d("2024-06-29"));
+
+    let check = engine.check(&stays, d("2024-06-28")).unwrap();
+    assert_eq!(check.days_remaining, 0);
+    assert_eq!(check.verdict, Verdict::Within);
+    let check = engine.check(&stays, d("2024-06-29")).unwrap();
+    assert_eq!(check.days_over, 1);
+    assert_eq!(check.verdict, Verdict::Exceeds);
+    // A new visit may start the next day, even though 180 days were spent in the last 180.
+    assert_eq!(engine.max_stay(&stays, d("2024-06-30")).unwrap(), 180);
+
+    // Only the current visit limits the days remaining.
+    let trips = [
+        Trip::new(d("2024-01-01"), d("2024-03-21")).unwrap(),
+        Trip::open(d("2024-05-01")),
+    ];
+    let stays = DateIntervalVec::from_trips(&trips, d("2024-05-20"));
+    let check = engine.check(&stays, d("2024-05-20")).unwrap();
+    assert_eq!(check.days_used, 101);
+    assert_eq!(check.days_remaining, 160);
+    assert_eq!(engine.to_string(), "up to 180 days per stay");
+}
+
+#[test]
+fn counting_rules() {
+    let trips = [
+        Trip::new(d("2024-03-01"), d("2024-03-10")).unwrap(),
+        Trip::new(d("2024-03-15"), d("2024-03-15")).unwrap(),
+    ];
+    let days = |counting| {
+        let regime = Regime {
+            counting,
+            ..Regime::default()
+        };
+        let (trips, _) = regime.counted_trips(&trips, &[]);
+        let stays = DateIntervalVec::from_trips(&trips, d("2024-03-31"));
+        regime
+            .engine()
+            .check(&stays, d("2024-03-31"))
+            .unwrap()
+            .days_used
+    };
+    assert_eq!(Regime::default().counting, Convention::AnyPart);
+    assert_eq!(days(Convention::AnyPart), 11);
+    assert_eq!(days(Convention::Midnights), 9);
+    assert_eq!(days(Convention::ExcludingTransit), 10);
+}
+
+#[test]
+fn days_before_the_effective_date_are_not_counted() {
+    let regime = Regime::default();
+    assert_eq!(regime.name, "schengen");
+    let trips = [
+        Trip::new(d("2013-05-01"), d("2013-06-30")).unwrap(),
+        Trip::new(d("2013-10-01"), d("2013-10-31")).unwrap(),
+    ];
+    let trips = regime.applicable_trips(&trips);
+
+    assert_eq!(trips.len(), 1);
+    assert_eq!(trips[0].entry, d("2013-10-18"));
+    let stays = DateIntervalVec::from_trips(&trips, d("2013-10-31"));
+    let check = regime.engine().check(&stays, d("2013-10-31")).unwrap();
+    assert_eq!(check.days_used, 14);
+}
+
+#[test]
+fn unknown_regime() {
+    let err = "mars".parse::<Regime>().unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Unknown regime 'mars', choose one of: schengen, turkey, uk-visitor"
+    );
+}
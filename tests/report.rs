@@ -0,0 +1,46 @@
+//! HTML report.
+
+use chrono::NaiveDate;
+use multi_visa_calc::{report, Engine, Trip, TripInfo};
+
+fn d(s: &str) -> NaiveDate {
+    NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+}
+
+#[test]
+fn usage_covers_history_and_plans() {
+    let trips = [Trip::new(d("2023-06-01"), d("2023-06-10")).unwrap()];
+    let planned = [Trip::new(d("2024-03-01"), d("2024-06-30")).unwrap()];
+    let report = Engine::default()
+        .travel_report(&trips, &planned, d("2024-01-01"))
+        .unwrap();
+
+    assert_eq!(report.usage.first().unwrap().date, d("2023-06-01"));
+    assert_eq!(report.usage.last().unwrap().date, d("2024-06-30"));
+    assert_eq!(report.check.days_used, 0);
+    let breach = report.planned[0].breach.unwrap();
+    assert_eq!(breach.first_day, d("2024-05-30"));
+    assert_eq!(report.usage.last().unwrap().days_used, 122);
+}
+
+#[test]
+fn html_is_self_contained() {
+    let info = TripInfo {
+        label: Some("R&D <kick-off>".to_string()),
+        ..TripInfo::default()
+    };
+    let trips = [Trip::new(d("2024-01-01"), d("2024-01-10"))
+        .unwrap()
+        .with_info(info)];
+    let report = Engine::default()
+        .travel_report(&trips, &[], d("2024-02-01"))
+        .unwrap();
+    let mut html = Vec::new();
+    report::write_html(&mut html, &report).unwrap();
+    let html = String::from_utf8(html).unwrap();
+
+    assert!(html.starts_with("<!DOCTYPE html>"));
+    assert_eq!(html.matches("<svg").count(), 2);
+    assert!(html.contains("R&amp;D &lt;kick-off&gt;"));
+    assert!(!html.contains("<script") && !html.contains("<link") && !html.contains("src="));
+}
@@ -0,0 +1,86 @@
+//! US substantial presence test.
+
+use chrono::{Days, NaiveDate};
+use multi_visa_calc::spt::presence_test;
+use multi_visa_calc::{DateInterval, Trip, TripInfo};
+
+fn d(s: &str) -> NaiveDate {
+    NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+}
+
+fn us(entry: &str, num_days: u64) -> Trip {
+    let info = TripInfo {
+        country: Some("US".to_string()),
+        ..TripInfo::default()
+    };
+    let entry = d(entry);
+    Trip::new(entry, entry + Days::new(num_days - 1))
+        .unwrap()
+        .with_info(info)
+}
+
+#[test]
+fn preceding_years_are_weighted() {
+    let trips = [
+        us("2022-03-01", 120),
+        us("2023-03-01", 120),
+        us("2024-03-01", 120),
+    ];
+    let test = presence_test(&trips, 2024, &[], d("2024-12-31")).unwrap();
+    assert_eq!(
+        (
+            test.current_days,
+            test.first_preceding_days,
+            test.second_preceding_days
+        ),
+        (120, 120, 120)
+    );
+    assert_eq!(test.weighted_days, 180.0);
+    assert!(!test.met);
+    assert_eq!(test.days_to_meet, Some(3));
+
+    // 62 days and a third of 365 are 183 days and two thirds.
+    let trips = [us("2023-01-01", 365), us("2024-03-01", 62)];
+    let test = presence_test(&trips, 2024, &[], d("2024-12-31")).unwrap();
+    assert!((test.weighted_days - 183.67).abs() < 0.01);
+    assert!(test.met);
+    assert_eq!(test.days_to_meet, None);
+}
+
+#[test]
+fn current_year_needs_31_days() {
+    let trips = [
+        us("2022-01-01", 365),
+        us("2023-01-01", 365),
+        us("2024-03-01", 30),
+    ];
+    let test = presence_test(&trips, 2024, &[], d("2024-12-31")).unwrap();
+    assert!(test.weighted_days >= 183.0);
+    assert!(!test.met);
+    assert_eq!(test.days_to_meet, Some(1));
+}
+
+#[test]
+fn exempt_and_transit_days_are_not_counted() {
+    let mut usa = us("2024-09-01", 5);
+    usa.info.country = Some("United States".to_string());
+    let trips = [
+        us("2024-03-01", 60),
+        // A connection between two other countries.
+        us("2024-06-01", 1),
+        usa,
+        // Trips without a country are not trips to the US.
+        Trip::new(d("2024-10-01"), d("2024-10-31")).unwrap(),
+        // Not a trip to the US.
+        Trip::new(d("2024-07-01"), d("2024-08-31"))
+            .unwrap()
+            .with_info(TripInfo {
+                country: Some("DE".to_string()),
+                ..TripInfo::default()
+            }),
+    ];
+    let exempt = [DateInterval::new(d("2024-02-01"), d("2024-03-10")).unwrap()];
+    let test = presence_test(&trips, 2024, &exempt, d("2024-12-31")).unwrap();
+    assert_eq!(test.current_days, 50 + 5);
+    assert_eq!(test.exempt_days, 10);
+}
@@ -0,0 +1,96 @@
+//! UK statutory residence test.
+
+use chrono::NaiveDate;
+use multi_visa_calc::srt::{self, Test};
+use multi_visa_calc::{Declaration, Tie, Trip, TripInfo};
+
+fn d(s: &str) -> NaiveDate {
+    NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+}
+
+fn uk(entry: &str, exit: &str) -> Trip {
+    let info = TripInfo {
+        country: Some("GB".to_string()),
+        ..TripInfo::default()
+    };
+    Trip::new(d(entry), d(exit)).unwrap().with_info(info)
+}
+
+#[test]
+fn midnights_are_counted_in_the_tax_year() {
+    let trips = [
+        // Only the midnights from 6 April are in the tax year.
+        uk("2024-04-01", "2024-04-10"),
+        // Leaving and coming back on the same day.
+        uk("2024-05-01", "2024-05-03"),
+        uk("2024-05-03", "2024-05-05"),
+        // A day trip has no midnight.
+        uk("2024-06-01", "2024-06-01"),
+    ];
+    let year = srt::tax_year(2024).unwrap();
+    assert_eq!(year.start(), d("2024-04-06"));
+    assert_eq!(year.end(), d("2025-04-05"));
+    assert_eq!(srt::tax_year_of(d("2025-04-05")), 2024);
+    assert_eq!(srt::uk_days(&trips, year, d("2025-04-05")), 4 + 4);
+}
+
+#[test]
+fn automatic_tests() {
+    let trips = [uk("2024-05-01", "2024-06-10")];
+    let test = srt::residence_test(&trips, 2024, &Declaration::default(), d("2025-04-05")).unwrap();
+    assert_eq!(test.tax_year, "2024-25");
+    assert_eq!(test.uk_days, 40);
+    assert_eq!(test.test, Test::SecondAutomaticOverseas);
+    assert!(!test.resident);
+
+    let mut trips = [
+        uk("2024-05-01", "2024-11-01"),
+        // Trips without a country are not trips to the UK.
+        Trip::new(d("2025-01-01"), d("2025-03-01")).unwrap(),
+    ];
+    trips[0].info.country = Some("United Kingdom".to_string());
+    assert_eq!(srt::uk_trips(&trips).len(), 1);
+    trips[1].info.country = Some("uk".to_string());
+    assert_eq!(srt::uk_trips(&trips).len(), 2);
+    trips[1].info.country = None;
+    let test = srt::residence_test(&trips, 2024, &Declaration::default(), d("2025-04-05")).unwrap();
+    assert_eq!(test.uk_days, 184);
+    assert_eq!(test.test, Test::FirstAutomaticUk);
+    assert!(test.resident);
+}
+
+#[test]
+fn sufficient_ties_include_the_90_day_tie() {
+    let trips = [
+        uk("2023-05-01", "2023-08-31"),
+        uk("2024-05-01", "2024-07-01"),
+        // Not a trip to the UK.
+        Trip::new(d("2024-08-01"), d("2024-12-31"))
+            .unwrap()
+            .with_info(TripInfo {
+                country: Some("FR".to_string()),
+                ..TripInfo::default()
+            }),
+    ];
+    let declaration = Declaration {
+        ties: vec![Tie::Family, Tie::Country],
+        resident_before: false,
+        works_abroad: false,
+    };
+    let test = srt::residence_test(&trips, 2024, &declaration, d("2025-04-05")).unwrap();
+    assert_eq!(test.uk_days, 61);
+    assert_eq!(test.test, Test::SufficientTies);
+    // The country tie only counts for those resident before.
+    assert_eq!(test.ties, [Tie::Family, Tie::NinetyDay]);
+    assert_eq!(test.ties_needed, Some(4));
+    assert!(!test.resident);
+
+    let declaration = Declaration {
+        resident_before: true,
+        ..declaration
+    };
+    let test = srt::residence_test(&trips, 2024, &declaration, d("2025-04-05")).unwrap();
+    assert_eq!(test.ties.len(), 3);
+    assert_eq!(test.ties_needed, Some(3));
+    assert!(test.resident);
+}
@@ -0,0 +1,79 @@
+//! Days of presence per tax year.
+
+use chrono::NaiveDate;
+use multi_visa_calc::{Convention, Leg, TaxRule, Trip, TripInfo, YearStart};
+
+fn d(s: &str) -> NaiveDate {
+    NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+}
+
+#[test]
+fn counting_conventions() {
+    let trips = [
+        Trip::new(d("2024-03-01"), d("2024-03-10")).unwrap(),
+        Trip::new(d("2024-04-01"), d("2024-04-01")).unwrap(),
+    ];
+    let count = |convention| {
+        let rule = TaxRule {
+            convention,
+            ..TaxRule::default()
+        };
+        rule.count(&trips, &[], 2024..=2024, d("2024-12-31"))
+            .unwrap()[0]
+            .days
+    };
+    assert_eq!(count(Convention::AnyPart), 11);
+    assert_eq!(count(Convention::Midnights), 9);
+    assert_eq!(count(Convention::ExcludingTransit), 10);
+}
+
+#[test]
+fn tax_years() {
+    let year_start: YearStart = "07-01".parse().unwrap();
+    assert_eq!(year_start.year_of(d("2024-06-30")), 2023);
+    let period = year_start.period(2024).unwrap();
+    assert_eq!(period.start(), d("2024-07-01"));
+    assert_eq!(period.end(), d("2025-06-30"));
+    assert_eq!(year_start.name(2024), "2024-25");
+    assert_eq!(YearStart::JANUARY.name(2024), "2024");
+    assert!("02-29".parse::<YearStart>().is_err());
+}
+
+#[test]
+fn threshold_is_reached_with_planned_trips() {
+    let info = |country: &str| TripInfo {
+        country: Some(country.to_string()),
+        ..TripInfo::default()
+    };
+    let trips = [
+        Trip::new(d("2024-01-01"), d("2024-03-31"))
+            .unwrap()
+            .with_info(info("Spain")),
+        // Only the days in Spain count.
+        Trip::new(d("2024-05-01"), d("2024-05-31"))
+            .unwrap()
+            .with_info(info("FR"))
+            .with_legs(vec![Leg {
+                date: d("2024-05-21"),
+                country: "ES".to_string(),
+                border: None,
+            }])
+            .unwrap(),
+    ];
+    // Planned trips without a country are in the country of the rule.
+    let planned = [Trip::new(d("2024-09-01"), d("2024-12-31")).unwrap()];
+    let rule = TaxRule {
+        country: Some("ES".to_string()),
+        ..TaxRule::default()
+    };
+    let years = rule
+        .count(&trips, &planned, 2024..=2025, d("2024-06-01"))
+        .unwrap();
+    assert_eq!(years[0].days, 91 + 11);
+    assert_eq!(years[0].planned_days, 91 + 11 + 122);
+    // 81 more days are needed after the trips, reached on the 81st day from 1 September.
+    assert_eq!(years[0].threshold_reached, Some(d("2024-11-20")));
+    assert_eq!(years[1].tax_year, "2025");
+    assert_eq!(years[1].planned_days, 0);
+    assert_eq!(years[1].threshold_reached, None);
+}
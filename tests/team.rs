@@ -0,0 +1,102 @@
+//! Allowance of all travellers in a journal.
+
+use chrono::NaiveDate;
+use multi_visa_calc::{team, Engine, Journal, Regime, SortKey, TravellerStatus};
+
+fn d(s: &str) -> NaiveDate {
+    NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+}
+
+const JOURNAL: &str = r#"
+[[traveller]]
+name = "Dana"
+
+[[traveller.trip]]
+entry = 2024-05-01
+exit = 2024-06-30
+
+[[traveller]]
+name = "Carl"
+
+[[traveller.trip]]
+entry = 2024-04-01
+exit = 2024-06-29
+
+[[traveller]]
+name = "Bea"
+
+[[traveller.trip]]
+entry = 2024-03-01
+exit = 2024-06-30
+
+[[traveller]]
+name = "Abe"
+
+[[traveller.trip]]
+entry = 2024-05-01
+exit = 2024-06-30
+
+# Only trips to the zone are counted.
+[[traveller.trip]]
+entry = 2024-04-01
+exit = 2024-04-20
+country = "TR"
+
+[[traveller]]
+name = "Eve"
+"#;
+
+fn statuses() -> Vec<TravellerStatus> {
+    let journal = Journal::parse(JOURNAL).unwrap();
+    Engine::default()
+        .team_status(&journal, &Regime::default(), d("2024-06-30"))
+        .unwrap()
+}
+
+fn names(statuses: &[TravellerStatus]) -> Vec<&str> {
+    statuses.iter().map(|s| s.name.as_str()).collect()
+}
+
+#[test]
+fn status_of_every_traveller() {
+    let statuses = statuses();
+    assert_eq!(names(&statuses), ["Dana", "Carl", "Bea", "Abe", "Eve"]);
+    let used: Vec<_> = statuses.iter().map(|s| s.days_used).collect();
+    assert_eq!(used, [61, 90, 122, 61, 0]);
+    let remaining: Vec<_> = statuses.iter().map(|s| s.days_remaining).collect();
+    assert_eq!(remaining, [29, 0, 0, 29, 90]);
+    let compliant: Vec<_> = statuses.iter().map(|s| s.compliant).collect();
+    assert_eq!(compliant, [true, true, false, true, true]);
+    assert_eq!(statuses[1].earliest_entry, d("2024-09-28"));
+    assert_eq!(statuses[2].earliest_entry, d("2024-09-29"));
+    assert_eq!(statuses[4].earliest_entry, d("2024-06-30"));
+}
+
+#[test]
+fn sort_keys_break_ties_by_name() {
+    let mut statuses = statuses();
+    team::sort_statuses(&mut statuses, SortKey::Name);
+    assert_eq!(names(&statuses), ["Abe", "Bea", "Carl", "Dana", "Eve"]);
+    team::sort_statuses(&mut statuses, SortKey::DaysUsed);
+    assert_eq!(names(&statuses), ["Bea", "Carl", "Abe", "Dana", "Eve"]);
+    team::sort_statuses(&mut statuses, SortKey::DaysRemaining);
+    assert_eq!(names(&statuses), ["Bea", "Carl", "Abe", "Dana", "Eve"]);
+    team::sort_statuses(&mut statuses, SortKey::EarliestEntry);
+    assert_eq!(names(&statuses), ["Bea", "Carl", "Abe", "Dana", "Eve"]);
+}
+
+#[test]
+fn filters() {
+    let mut statuses = statuses();
+    team::filter_statuses(&mut statuses, Some(29), false);
+    assert_eq!(names(&statuses), ["Dana", "Carl", "Bea", "Abe"]);
+    team::filter_statuses(&mut statuses, Some(0), false);
+    assert_eq!(names(&statuses), ["Carl", "Bea"]);
+
+    let mut statuses = self::statuses();
+    team::filter_statuses(&mut statuses, None, true);
+    assert_eq!(names(&statuses), ["Bea"]);
+    let mut statuses = self::statuses();
+    team::filter_statuses(&mut statuses, None, false);
+    assert_eq!(statuses.len(), 5);
+}
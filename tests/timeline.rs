@@ -0,0 +1,50 @@
+//! Per-day allowance timeline.
+
+use chrono::NaiveDate;
+use multi_visa_calc::{timeline, DateInterval, DateIntervalVec, Engine, Trip};
+
+fn d(s: &str) -> NaiveDate {
+    NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+}
+
+fn recovery() -> Vec<timeline::TimelineDay> {
+    // 90 days from 2024-01-01 to 2024-03-30. The control period ending on 2024-06-28 starts on
+    // 2024-01-01, and from then on a day of the allowance is freed every day.
+    let trips = [Trip::new(d("2024-01-01"), d("2024-03-30")).unwrap()];
+    let stays = DateIntervalVec::from_trips(&trips, d("2024-07-01"));
+    let range = DateInterval::new(d("2024-06-27"), d("2024-07-01")).unwrap();
+    Engine::default().timeline(&stays, range).unwrap()
+}
+
+#[test]
+fn allowance_recovers_day_by_day() {
+    let days = recovery();
+    let used: Vec<_> = days.iter().map(|day| day.days_used).collect();
+    let remaining: Vec<_> = days.iter().map(|day| day.days_remaining).collect();
+    assert_eq!(used, [90, 90, 89, 88, 87]);
+    assert_eq!(remaining, [0, 0, 1, 2, 3]);
+    assert!(days.iter().all(|day| !day.in_zone));
+    assert_eq!(days[0].date, d("2024-06-27"));
+    assert_eq!(days[4].date, d("2024-07-01"));
+}
+
+#[test]
+fn csv_and_table() {
+    let days = recovery();
+    let mut csv = Vec::new();
+    timeline::write_csv(&mut csv, &days[1..3]).unwrap();
+    assert_eq!(
+        String::from_utf8(csv).unwrap(),
+        "date,in_zone,days_used,days_remaining\n\
+         2024-06-28,false,90,0\n\
+         2024-06-29,false,89,1\n"
+    );
+
+    let mut table = Vec::new();
+    timeline::write_table(&mut table, &days[2..3]).unwrap();
+    assert_eq!(
+        String::from_utf8(table).unwrap(),
+        "date        in zone  used  remaining\n\
+         2024-06-29  no         89          1\n"
+    );
+}
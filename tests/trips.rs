@@ -0,0 +1,83 @@
+//! Parsing and validation of trip lists.
+
+use chrono::NaiveDate;
+use multi_visa_calc::{open_trip, parse_trips, sort_trips, DateIntervalVec, Engine, Trip};
+
+fn d(s: &str) -> NaiveDate {
+    NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+}
+
+#[test]
+fn one_day_trips_and_same_day_turnarounds() {
+    let input = "\
+# one-day trip in the single-date format
+2024-01-01
+2024-01-01
+2024-01-05 2024-01-10
+2024-01-10,2024-01-12
+";
+    let mut trips = parse_trips(input.as_bytes()).unwrap();
+    assert_eq!(sort_trips(&mut trips).unwrap(), 0);
+    assert_eq!(trips.len(), 3);
+    assert_eq!(trips[0].line, Some(2));
+    assert_eq!(trips[2].line, Some(5));
+
+    let stays = DateIntervalVec::from_trips(&trips, NaiveDate::MAX);
+    let check = Engine::default().check(&stays, d("2024-02-01")).unwrap();
+    // 1 + 6 + 3 days, the turnaround day 2024-01-10 counted once.
+    assert_eq!(check.days_used, 9);
+}
+
+#[test]
+fn duplicates_are_removed() {
+    let mut trips =
+        parse_trips("2024-01-01 2024-01-02\n2024-01-01 2024-01-02\n".as_bytes()).unwrap();
+    assert_eq!(sort_trips(&mut trips).unwrap(), 1);
+    assert_eq!(trips.len(), 1);
+}
+
+#[test]
+fn errors_point_at_lines() {
+    let err = parse_trips("2024-02-01\n2024-01-01 2024-01-02\n".as_bytes()).unwrap_err();
+    assert_eq!(err.to_string(), "Line 1: entry date has no exit date");
+
+    let err =
+        parse_trips("2024-01-01 2024-01-02\n\n2024-02-03 2024-02-01\n".as_bytes()).unwrap_err();
+    assert_eq!(err.to_string(), "Line 3");
+
+    let err = parse_trips("2024-01-01 2024-13-02\n".as_bytes()).unwrap_err();
+    assert_eq!(err.to_string(), "Line 1");
+
+    let mut trips =
+        parse_trips("2024-01-01 2024-01-10\n2024-01-05 2024-01-20\n".as_bytes()).unwrap();
+    let err = sort_trips(&mut trips).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "The trip from 2024-01-05 to 2024-01-20 on line 2 overlaps the trip from 2024-01-01 to \
+         2024-01-10 on line 1"
+    );
+}
+
+#[test]
+fn open_trip_counts_until_evaluation_date() {
+    let mut trips = parse_trips("2024-01-01 2024-01-30\n2024-03-01\n".as_bytes()).unwrap();
+    sort_trips(&mut trips).unwrap();
+    assert_eq!(
+        open_trip(&trips),
+        Some(&Trip::open(d("2024-03-01")).at_line(2))
+    );
+
+    let engine = Engine::default();
+    let stays = DateIntervalVec::from_trips(&trips, d("2024-03-10"));
+    assert_eq!(engine.check(&stays, d("2024-03-10")).unwrap().days_used, 40);
+
+    // 30 days used before the open trip leave 60 days for it.
+    let history = DateIntervalVec::from_trips(&trips[..1], d("2024-03-10"));
+    assert_eq!(
+        engine.last_day(&history, d("2024-03-01")).unwrap(),
+        Some(d("2024-04-29"))
+    );
+
+    let mut trips = parse_trips("2024-03-01\n2024-03-01\n2024-02-01\n".as_bytes()).unwrap();
+    assert!(sort_trips(&mut trips).is_err());
+}
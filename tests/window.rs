@@ -0,0 +1,142 @@
+//! Boundary behaviour of the rolling window: "any 180-day period ending today, today included".
+
+use chrono::NaiveDate;
+use multi_visa_calc::{DateInterval, DateIntervalVec, Engine, Trip, Verdict};
+
+fn d(s: &str) -> NaiveDate {
+    NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+}
+
+fn stays(dates: &[&str]) -> DateIntervalVec {
+    let trips: Vec<_> = dates
+        .chunks(2)
+        .map(|pair| Trip::new(d(pair[0]), d(pair[1])).unwrap())
+        .collect();
+    DateIntervalVec::from_trips(&trips, NaiveDate::MAX)
+}
+
+#[test]
+fn control_period_is_180_days_including_end_date() {
+    let control_period = Engine::default().control_period(d("2024-06-30")).unwrap();
+    assert_eq!(
+        control_period,
+        DateInterval::new(d("2024-01-03"), d("2024-06-30")).unwrap()
+    );
+    assert_eq!(control_period.abs_num_days(), 180);
+}
+
+#[test]
+fn entry_and_exit_days_both_count() {
+    let check = Engine::default()
+        .check(&stays(&["2024-01-01", "2024-01-01"]), d("2024-01-01"))
+        .unwrap();
+    assert_eq!(check.days_used, 1);
+
+    let check = Engine::default()
+        .check(&stays(&["2024-01-01", "2024-01-02"]), d("2024-02-01"))
+        .unwrap();
+    assert_eq!(check.days_used, 2);
+}
+
+#[test]
+fn first_day_of_control_period_counts() {
+    // The control period ending on 2024-06-30 starts on 2024-01-03.
+    let check = Engine::default()
+        .check(&stays(&["2024-01-01", "2024-01-03"]), d("2024-06-30"))
+        .unwrap();
+    assert_eq!(check.days_used, 1);
+
+    let check = Engine::default()
+        .check(&stays(&["2024-01-01", "2024-01-02"]), d("2024-06-30"))
+        .unwrap();
+    assert_eq!(check.days_used, 0);
+    assert!(check.intervals.is_empty());
+}
+
+#[test]
+fn ninety_days_are_allowed_and_ninety_one_are_not() {
+    let check = Engine::default()
+        .check(&stays(&["2024-01-01", "2024-03-30"]), d("2024-03-30"))
+        .unwrap();
+    assert_eq!(check.days_used, 90);
+    assert_eq!(check.days_remaining, 0);
+    assert_eq!(check.verdict, Verdict::Within);
+
+    let check = Engine::default()
+        .check(&stays(&["2024-01-01", "2024-03-31"]), d("2024-03-31"))
+        .unwrap();
+    assert_eq!(check.days_used, 91);
+    assert_eq!(check.days_remaining, 0);
+    assert_eq!(check.verdict, Verdict::Exceeds);
+}
+
+#[test]
+fn earliest_entry_after_full_allowance() {
+    // 90 days from 2024-01-01 to 2024-03-30. On 2024-06-28 the control period still starts on
+    // 2024-01-01, on 2024-06-29 it starts on 2024-01-02 and a day of the allowance is freed.
+    let engine = Engine::default();
+    let history = stays(&["2024-01-01", "2024-03-30"]);
+    assert!(!engine.is_allowed_on(&history, d("2024-06-28")).unwrap());
+    assert!(engine.is_allowed_on(&history, d("2024-06-29")).unwrap());
+    assert_eq!(
+        engine.earliest_entry(&history, d("2024-04-01")).unwrap(),
+        d("2024-06-29")
+    );
+}
+
+#[test]
+fn max_stay_slides_with_the_window() {
+    let engine = Engine::default();
+    let history = stays(&["2024-01-01", "2024-03-30"]);
+    // Days are freed at the same rate as they are used.
+    assert_eq!(engine.max_stay(&history, d("2024-06-29")).unwrap(), 90);
+    assert_eq!(engine.max_stay(&history, d("2024-06-28")).unwrap(), 0);
+
+    // 60 days used, all of them within the control period of the next 30 days.
+    let history = stays(&["2024-03-01", "2024-04-29"]);
+    assert_eq!(engine.max_stay(&history, d("2024-05-10")).unwrap(), 30);
+}
+
+#[test]
+fn period_and_allowance_are_configurable() {
+    let engine = Engine::new(30, 10);
+    let control_period = engine.control_period(d("2024-01-30")).unwrap();
+    assert_eq!(control_period.start(), d("2024-01-01"));
+
+    let history = stays(&["2023-12-31", "2024-01-09"]);
+    let check = engine.check(&history, d("2024-01-30")).unwrap();
+    assert_eq!(check.days_used, 9);
+    assert_eq!(check.days_remaining, 1);
+    assert_eq!(
+        engine.earliest_entry(&history, d("2024-01-10")).unwrap(),
+        d("2024-01-30")
+    );
+    assert_eq!(engine.max_stay(&history, d("2024-01-29")).unwrap(), 0);
+    assert_eq!(engine.max_stay(&history, d("2024-01-30")).unwrap(), 10);
+
+    assert!(Engine::new(0, 10).control_period(d("2024-01-30")).is_err());
+    assert!(Engine::new(99_999_999_999, 90)
+        .control_period(d("2024-01-30"))
+        .is_err());
+}
+
+#[test]
+fn commission_worked_example() {
+    // Worked example of the European Commission's Practical Handbook for Border Guards: a
+    // multiple-entry visa valid from 18.4.2010 to 18.4.2011, a first stay of 3 days from 19.4.2010
+    // and a second stay of 86 days from 18.6.2010 to 11.9.2010.
+    let engine = Engine::default();
+    let history = stays(&["2010-04-19", "2010-04-21", "2010-06-18", "2010-09-11"]);
+
+    // On 18.9.2010 the last 180 days are 23.3.2010 to 18.9.2010, with 3 + 86 = 89 days of stay,
+    // so the person may enter for one day.
+    let check = engine.check(&history, d("2010-09-18")).unwrap();
+    assert_eq!(
+        check.control_period,
+        DateInterval::new(d("2010-03-23"), d("2010-09-18")).unwrap()
+    );
+    assert_eq!(check.days_used, 89);
+    assert_eq!(check.days_remaining, 1);
+    assert!(engine.is_allowed_on(&history, d("2010-09-18")).unwrap());
+    assert_eq!(engine.max_stay(&history, d("2010-09-18")).unwrap(), 1);
+}